@@ -2,7 +2,8 @@
 //!
 //! Groups:
 //!   diff              realistic change shapes at web-monitoring sizes
-//!   diff_heavy        realistic but adversarial (dense edits, unique lines)
+//!   diff_heavy        realistic but adversarial (dense edits, unique lines),
+//!                     also under the patience and histogram algorithms
 //!   diff_pathological worst cases (disjoint alphabets, random binary,
 //!                     repetitive half-match traps), each also with a
 //!                     deadline where the deadline changes the code path
@@ -19,7 +20,7 @@ use criterion::measurement::WallTime;
 use criterion::{
     criterion_group, criterion_main, BatchSize, BenchmarkGroup, Criterion, Throughput,
 };
-use diff_match_patch::Dmp;
+use diff_match_patch::{DiffAlgorithm, Dmp};
 use dmp_bench as ds;
 use std::hint::black_box;
 use std::time::Duration;
@@ -41,6 +42,30 @@ fn bench_diff(
     });
 }
 
+/// `bench_diff` under another line alignment algorithm, after checking that
+/// its diff rebuilds both texts.
+fn bench_diff_algorithm(
+    g: &mut BenchmarkGroup<'_, WallTime>,
+    id: &str,
+    pair: &(String, String),
+    algorithm: DiffAlgorithm,
+) {
+    let (t1, t2) = pair;
+    let mut d = Dmp::new();
+    d.diff_algorithm = algorithm;
+    let mut diffs = d.diff_main(t1, t2, true);
+    assert_eq!(&d.diff_text1(&mut diffs), t1, "{id} must rebuild text1");
+    assert_eq!(&d.diff_text2(&mut diffs), t2, "{id} must rebuild text2");
+    g.throughput(Throughput::Bytes((t1.len() + t2.len()) as u64));
+    g.bench_function(id, |b| {
+        b.iter(|| {
+            let mut d = Dmp::new();
+            d.diff_algorithm = algorithm;
+            d.diff_main(black_box(t1), black_box(t2), true)
+        })
+    });
+}
+
 /// The pre-restructure bench ids, kept verbatim so historical baselines
 /// remain comparable.
 fn legacy(c: &mut Criterion) {
@@ -48,13 +73,13 @@ fn legacy(c: &mut Criterion) {
     let (t1, t2) = &pair;
     c.bench_function("diff_main/interleaved-2k", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.diff_main(black_box(t1), black_box(t2), true)
         })
     });
     c.bench_function("patch_make+apply/interleaved-2k", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             let mut patches = d.patch_make1(t1, t2);
             d.patch_apply(&mut patches, t1)
         })
@@ -116,6 +141,24 @@ fn diff_heavy(c: &mut Criterion) {
         None,
     );
     bench_diff(&mut g, "unique_lines_180k", &ds::pair_unique_lines(), None);
+    // Line alignment algorithms on the line-heavy shapes.
+    for (name, algorithm) in [
+        ("patience", DiffAlgorithm::Patience),
+        ("histogram", DiffAlgorithm::Histogram),
+    ] {
+        bench_diff_algorithm(
+            &mut g,
+            &format!("code_rename_18k_{name}"),
+            &ds::pair_code_rename(),
+            algorithm,
+        );
+        bench_diff_algorithm(
+            &mut g,
+            &format!("unique_lines_180k_{name}"),
+            &ds::pair_unique_lines(),
+            algorithm,
+        );
+    }
     bench_diff(&mut g, "one_line_soup_16k", &ds::pair_one_line_soup(), None);
     g.finish();
 }
@@ -163,7 +206,7 @@ fn cleanup(c: &mut Criterion) {
         b.iter_batched(
             || scattered.clone(),
             |mut diffs| {
-                let mut d = Dmp::new();
+                let d = Dmp::new();
                 d.diff_cleanup_semantic(&mut diffs);
                 diffs
             },
@@ -186,7 +229,7 @@ fn cleanup(c: &mut Criterion) {
         b.iter_batched(
             || ds::diffs_lossless_slide(2_000),
             |mut diffs| {
-                let mut d = Dmp::new();
+                let d = Dmp::new();
                 d.diff_cleanup_semantic_lossless(&mut diffs);
                 diffs
             },
@@ -197,7 +240,7 @@ fn cleanup(c: &mut Criterion) {
         b.iter_batched(
             || ds::diffs_merge_churn(1_500),
             |mut diffs| {
-                let mut d = Dmp::new();
+                let d = Dmp::new();
                 d.diff_cleanup_merge(&mut diffs);
                 diffs
             },
@@ -218,18 +261,18 @@ fn patch(c: &mut Criterion) {
 
     g.bench_function("make_scattered_50k", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.patch_make1(black_box(&t1), black_box(&t2))
         })
     });
     g.bench_function("make_html_90k", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.patch_make1(black_box(&h1), black_box(&h2))
         })
     });
 
-    let mut dmp = Dmp::new();
+    let dmp = Dmp::new();
     let mut patches = dmp.patch_make1(&t1, &t2);
 
     // Sanity outside the timed loops: the three apply scenarios must all
@@ -256,19 +299,19 @@ fn patch(c: &mut Criterion) {
 
     g.bench_function("apply_clean_50k", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.patch_apply(black_box(&mut patches), black_box(&t1))
         })
     });
     g.bench_function("apply_shifted_50k", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.patch_apply(black_box(&mut patches), black_box(&shifted))
         })
     });
     g.bench_function("apply_fuzzed_50k", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.patch_apply(black_box(&mut patches), black_box(&fuzzed))
         })
     });
@@ -282,7 +325,7 @@ fn wire(c: &mut Criterion) {
         .sample_size(30);
 
     let (t1, t2) = ds::pair_scattered();
-    let mut dmp = Dmp::new();
+    let dmp = Dmp::new();
     let mut diffs = dmp.diff_main(&t1, &t2, true);
     let delta = dmp.diff_todelta(&mut diffs);
     let mut patches = dmp.patch_make1(&t1, &t2);
@@ -290,19 +333,19 @@ fn wire(c: &mut Criterion) {
 
     g.bench_function("todelta_scattered", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.diff_todelta(black_box(&mut diffs))
         })
     });
     g.bench_function("fromdelta_scattered", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.diff_from_delta(black_box(&t1), black_box(&delta))
         })
     });
     g.bench_function("patch_to_text_scattered", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.patch_to_text(black_box(&mut patches))
         })
     });
@@ -310,7 +353,7 @@ fn wire(c: &mut Criterion) {
         b.iter_batched(
             || patch_text.clone(),
             |text| {
-                let mut d = Dmp::new();
+                let d = Dmp::new();
                 d.patch_from_text(text)
             },
             BatchSize::SmallInput,
@@ -327,7 +370,7 @@ fn match_(c: &mut Criterion) {
 
     let (hay, fuzzy_pattern, loc) = ds::match_case();
     {
-        let mut d = Dmp::new();
+        let d = Dmp::new();
         assert_ne!(
             d.match_main(&hay, &fuzzy_pattern, loc),
             -1,
@@ -336,7 +379,7 @@ fn match_(c: &mut Criterion) {
     }
     g.bench_function("bitap_fuzzy_50k", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.match_main(black_box(&hay), black_box(&fuzzy_pattern), black_box(loc))
         })
     });
@@ -350,7 +393,7 @@ fn match_(c: &mut Criterion) {
     let near_loc = loc;
     g.bench_function("exact_near_50k", |b| {
         b.iter(|| {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             d.match_main(
                 black_box(&hay),
                 black_box(&exact_pattern),
@@ -10,7 +10,7 @@
 //! mutates its input clone it inside the loop; the Clone frames are visible
 //! in the flamegraph and marked with * in --list.
 
-use diff_match_patch::Dmp;
+use diff_match_patch::{DiffAlgorithm, Dmp};
 use dmp_bench as ds;
 use std::collections::{HashMap, HashSet};
 use std::hint::black_box;
@@ -22,6 +22,8 @@ const NAMES: &[&str] = &[
     "diff_code_rename",
     "diff_many_small",
     "diff_unique_lines",
+    "diff_code_rename_histogram",
+    "diff_unique_lines_histogram",
     "diff_cjk",
     "diff_soup",
     "diff_disjoint",
@@ -46,6 +48,14 @@ fn diff_workload(pair: (String, String), timeout: Option<f32>) -> Box<dyn FnMut(
     })
 }
 
+fn histogram_workload(pair: (String, String)) -> Box<dyn FnMut()> {
+    Box::new(move || {
+        let mut d = Dmp::new();
+        d.diff_algorithm = DiffAlgorithm::Histogram;
+        black_box(d.diff_main(&pair.0, &pair.1, true));
+    })
+}
+
 fn build(name: &str) -> Option<Box<dyn FnMut()>> {
     Some(match name {
         "diff_scattered" => diff_workload(ds::pair_scattered(), None),
@@ -53,6 +63,8 @@ fn build(name: &str) -> Option<Box<dyn FnMut()>> {
         "diff_code_rename" => diff_workload(ds::pair_code_rename(), None),
         "diff_many_small" => diff_workload(ds::pair_many_small_edits(), None),
         "diff_unique_lines" => diff_workload(ds::pair_unique_lines(), None),
+        "diff_code_rename_histogram" => histogram_workload(ds::pair_code_rename()),
+        "diff_unique_lines_histogram" => histogram_workload(ds::pair_unique_lines()),
         "diff_cjk" => diff_workload(ds::pair_cjk(), None),
         "diff_soup" => diff_workload(ds::pair_one_line_soup(), None),
         "diff_disjoint" => diff_workload(ds::pair_disjoint(2_000), None),
@@ -62,20 +74,20 @@ fn build(name: &str) -> Option<Box<dyn FnMut()>> {
             let (t1, t2) = ds::pair_scattered();
             let diffs = Dmp::new().diff_main(&t1, &t2, true);
             Box::new(move || {
-                let mut d = Dmp::new();
+                let d = Dmp::new();
                 let mut work = diffs.clone();
                 d.diff_cleanup_semantic(&mut work);
                 black_box(work);
             })
         }
         "cleanup_lossless" => Box::new(move || {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             let mut work = ds::diffs_lossless_slide(2_000);
             d.diff_cleanup_semantic_lossless(&mut work);
             black_box(work);
         }),
         "cleanup_merge" => Box::new(move || {
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             let mut work = ds::diffs_merge_churn(1_500);
             d.diff_cleanup_merge(&mut work);
             black_box(work);
@@ -83,7 +95,7 @@ fn build(name: &str) -> Option<Box<dyn FnMut()>> {
         "patch_make" => {
             let (t1, t2) = ds::pair_scattered();
             Box::new(move || {
-                let mut d = Dmp::new();
+                let d = Dmp::new();
                 black_box(d.patch_make1(&t1, &t2));
             })
         }
@@ -96,17 +108,17 @@ fn build(name: &str) -> Option<Box<dyn FnMut()>> {
                 _ => t1,
             };
             Box::new(move || {
-                let mut d = Dmp::new();
+                let d = Dmp::new();
                 black_box(d.patch_apply(&mut patches, &source));
             })
         }
         "wire_roundtrip" => {
             let (t1, t2) = ds::pair_scattered();
-            let mut d = Dmp::new();
+            let d = Dmp::new();
             let mut diffs = d.diff_main(&t1, &t2, true);
             let mut patches = d.patch_make1(&t1, &t2);
             Box::new(move || {
-                let mut d = Dmp::new();
+                let d = Dmp::new();
                 let delta = d.diff_todelta(&mut diffs);
                 black_box(d.diff_from_delta(&t1, &delta));
                 let text = d.patch_to_text(&mut patches);
@@ -116,7 +128,7 @@ fn build(name: &str) -> Option<Box<dyn FnMut()>> {
         "match_bitap" => {
             let (hay, pattern, loc) = ds::match_case();
             Box::new(move || {
-                let mut d = Dmp::new();
+                let d = Dmp::new();
                 black_box(d.match_main(&hay, &pattern, loc));
             })
         }
@@ -6,7 +6,7 @@
 //! Everything is seeded xorshift, no ambient entropy: criterion baselines
 //! and flamegraphs stay comparable across runs and machines.
 
-use diff_match_patch::{Diff, Patch};
+use diff_match_patch::{Diff, Operation, Patch};
 
 /// xorshift64. Deterministic, dependency-free; NOT for anything but datasets.
 pub struct Rng(u64);
@@ -448,9 +448,9 @@ pub fn pair_one_line_soup() -> (String, String) {
 /// vectors at every step — the pass's quadratic worst case.
 pub fn diffs_lossless_slide(n: usize) -> Vec<Diff> {
     vec![
-        Diff::new(0, "x".repeat(n)),
-        Diff::new(1, "x".repeat(n)),
-        Diff::new(0, "x".repeat(n)),
+        Diff::new(Operation::Equal, "x".repeat(n)),
+        Diff::new(Operation::Insert, "x".repeat(n)),
+        Diff::new(Operation::Equal, "x".repeat(n)),
     ]
 }
 
@@ -459,9 +459,18 @@ pub fn diffs_lossless_slide(n: usize) -> Vec<Diff> {
 pub fn diffs_merge_churn(runs: usize) -> Vec<Diff> {
     let mut v = Vec::with_capacity(runs * 3);
     for i in 0..runs {
-        v.push(Diff::new(-1, ((b'a' + (i % 13) as u8) as char).to_string()));
-        v.push(Diff::new(1, ((b'n' + (i % 13) as u8) as char).to_string()));
-        v.push(Diff::new(0, ((b'A' + (i % 26) as u8) as char).to_string()));
+        v.push(Diff::new(
+            Operation::Delete,
+            ((b'a' + (i % 13) as u8) as char).to_string(),
+        ));
+        v.push(Diff::new(
+            Operation::Insert,
+            ((b'n' + (i % 13) as u8) as char).to_string(),
+        ));
+        v.push(Diff::new(
+            Operation::Equal,
+            ((b'A' + (i % 26) as u8) as char).to_string(),
+        ));
     }
     v
 }
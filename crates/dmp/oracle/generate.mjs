@@ -94,6 +94,7 @@ const cases = CASES.map(([name, text1, text2, applyTo]) => {
   const delta = dmp.diff_toDelta(diff);
   const diffSemantic = diff.map((d) => [d[0], d[1]]);
   dmp.diff_cleanupSemantic(diffSemantic);
+  const prettyHtml = dmp.diff_prettyHtml(diff);
 
   const patches = dmp.patch_make(text1, text2);
   const patchText = dmp.patch_toText(patches);
@@ -109,6 +110,7 @@ const cases = CASES.map(([name, text1, text2, applyTo]) => {
     diff, // [[op, text], ...] with op in {-1,0,1}
     diffSemantic,
     delta,
+    prettyHtml,
     patchText,
     patchApplied: applied,
     patchResults: results,
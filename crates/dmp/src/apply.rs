@@ -0,0 +1,109 @@
+// Detailed patch application: for each patch, where it was expected, where
+// Bitap found it, how good the match was and how it was applied or why it
+// was dropped. The loop itself is `patch::apply_tokens`, which every
+// `patch_apply` variant also runs.
+
+use crate::types::{Dmp, Patch};
+
+/// How closely `patch_apply` must find a patch's old text (context and
+/// deletions) before applying it; see `Dmp::patch_apply_policy`.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ApplyPolicy {
+    /// Only verbatim at the expected location (the patch's own start,
+    /// shifted by the drift of the patch before it).
+    ExactAt,
+    /// Only verbatim, at most this many chars from the expected location;
+    /// the nearest copy wins.
+    ExactWithin(usize),
+    /// Bitap search around the expected location, within
+    /// `match_threshold` and `match_distance`, merging into text that has
+    /// changed (the historical behavior).
+    #[default]
+    Fuzzy,
+}
+
+/// How an applied patch was merged into the text.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApplyMethod {
+    /// The located text equalled the patch's old text; the new text was
+    /// spliced in whole.
+    Splice,
+    /// The located text differed; the patch's edits were mapped onto it
+    /// through a diff of the two.
+    Diff,
+}
+
+/// Why a patch was not applied.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApplyFailure {
+    /// No match scored within `match_threshold` of the expected location.
+    NoMatch,
+    /// A patch longer than `match_maxbits` matched at its start, but its
+    /// trailing context was missing or came before its start.
+    BadTrailingContext,
+    /// Both ends of a long patch were found, but the text between them
+    /// differed by more than `patch_delete_threshold`.
+    OverDeleteThreshold,
+    /// `ApplyPolicy::ExactAt`: the text at the expected location differs
+    /// from the patch's old text.
+    NotAtLocation,
+    /// `ApplyPolicy::ExactWithin`: no verbatim copy of the patch's old text
+    /// within the allowed offset.
+    NotWithinOffset,
+}
+
+/// What happened to one patch in [`Dmp::patch_apply_report`]. Locations are
+/// char offsets into the text as it stood when the patch was tried, i.e.
+/// after the patches before it were applied.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatchReport {
+    /// Where the patch was looked for: its own start, shifted by the drift
+    /// of the patch before it. Negative when its leading context reaches
+    /// into the padding before the text start.
+    pub expected: i32,
+    /// Where the match was found (`None` if none was).
+    pub actual: Option<i32>,
+    /// `actual - expected`: how far the text has drifted here.
+    pub delta: Option<i32>,
+    /// Bitap score of the match, from 0.0 (exact, in place) up to
+    /// `match_threshold`.
+    pub score: Option<f32>,
+    /// How the match was merged; set whenever a match was found, even if
+    /// the patch was then dropped as `OverDeleteThreshold`.
+    pub method: Option<ApplyMethod>,
+    /// Why the patch was dropped (`None` if it applied).
+    pub failure: Option<ApplyFailure>,
+}
+
+impl PatchReport {
+    pub fn applied(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// Applied verbatim where it was expected.
+    pub fn is_exact(&self) -> bool {
+        self.applied() && self.method == Some(ApplyMethod::Splice) && self.delta == Some(0)
+    }
+}
+
+impl Dmp {
+    /// `patch_apply`, reporting how each patch was placed. Patches longer
+    /// than `match_maxbits` are split first, as `patch_apply` does, and get
+    /// one report per piece. The patches are only read.
+    ///
+    /// Args:
+    /// patches: Vector of Patch objects.
+    /// text: Old text.
+    ///
+    /// Returns:
+    /// The new text, and a report per (split) patch.
+    pub fn patch_apply_report(&self, patches: &[Patch], text: &str) -> (String, Vec<PatchReport>) {
+        let mut text = text.to_string();
+        let reports = crate::patch::apply_in_place(self, patches, &mut text);
+        (text, reports)
+    }
+}
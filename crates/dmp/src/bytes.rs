@@ -0,0 +1,150 @@
+// Byte-level diff, delta and patch for input that is not UTF-8 (legacy
+// encodings, Latin-1 pages, binary blobs). The diff runs the byte recursion
+// of the ASCII fast path on arbitrary bytes. Byte patches are ordinary
+// `Patch` values whose text holds one char per byte (U+0000–U+00FF), so the
+// whole match/patch machinery applies unchanged; only the wire encodings
+// escape bytes instead of UTF-8.
+
+use crate::delta::{encode_uri_bytes, parse_delta};
+use crate::error::ParseError;
+use crate::seq::SeqDiff;
+use crate::types::{Dmp, Operation, Patch};
+use percent_encoding::percent_decode;
+
+impl Dmp {
+    /// Find the differences between two byte strings, with the same
+    /// algorithm (and line-mode speedup on `\n`) `diff_main` runs on text.
+    ///
+    /// Args:
+    /// old: Old bytes to be diffed.
+    /// new: New bytes to be diffed.
+    ///
+    /// Returns:
+    /// Vector of byte runs as changes.
+    pub fn diff_bytes(&self, old: &[u8], new: &[u8]) -> Vec<SeqDiff<u8>> {
+        crate::diff::diff_byte_tokens(self, old, new)
+            .into_iter()
+            .map(|d| SeqDiff::new(d.operation, d.data.iter().map(|&c| c as u8).collect()))
+            .collect()
+    }
+
+    /// `diff_todelta` for byte diffs: lengths count bytes and inserted bytes
+    /// are escaped one by one (`%XX` above ASCII). On ASCII input the output
+    /// equals the text delta.
+    ///
+    /// Args:
+    /// diffs: Vector of byte runs.
+    ///
+    /// Returns:
+    /// Delta text.
+    pub fn diff_todelta_bytes(&self, diffs: &[SeqDiff<u8>]) -> String {
+        let tokens: Vec<String> = diffs
+            .iter()
+            .map(|diff| match diff.operation {
+                Operation::Insert => format!("+{}", encode_uri_bytes(&diff.items)),
+                Operation::Delete => format!("-{}", diff.items.len()),
+                Operation::Equal => format!("={}", diff.items.len()),
+            })
+            .collect();
+        tokens.join("\t")
+    }
+
+    /// Decode a byte delta against the original bytes; see
+    /// `try_diff_from_delta` for the errors.
+    ///
+    /// Args:
+    /// old: Source bytes for the diff.
+    /// delta: Delta text.
+    ///
+    /// Returns:
+    /// Vector of byte runs.
+    pub fn diff_from_delta_bytes(
+        &self,
+        old: &[u8],
+        delta: &str,
+    ) -> Result<Vec<SeqDiff<u8>>, ParseError> {
+        let runs = parse_delta(old, delta, |param| {
+            Some(percent_decode(param.as_bytes()).collect())
+        })?;
+        Ok(runs
+            .into_iter()
+            .map(|(op, run)| SeqDiff::new(op, run))
+            .collect())
+    }
+
+    /// `patch_make1` for byte strings. The patches carry one char per byte;
+    /// serialize them with `patch_to_text_bytes`, not `patch_to_text`.
+    ///
+    /// Args:
+    /// old: Old bytes.
+    /// new: New bytes.
+    ///
+    /// Returns:
+    /// Vector of Patch objects.
+    pub fn patch_make_bytes(&self, old: &[u8], new: &[u8]) -> Vec<Patch> {
+        self.patch_make1(&widen(old), &widen(new))
+    }
+
+    /// `patch_apply` for byte patches (from `patch_make_bytes` or
+    /// `patch_from_text_bytes`).
+    ///
+    /// Args:
+    /// patches: Byte patches.
+    /// old: Old bytes.
+    ///
+    /// Returns:
+    /// The new bytes, and whether each patch applied.
+    ///
+    /// Panics if a patch holds a char above U+00FF (a text patch).
+    pub fn patch_apply_bytes(&self, patches: &[Patch], old: &[u8]) -> (Vec<u8>, Vec<bool>) {
+        let (chars, results) = self.patch_apply(&mut patches.to_vec(), &widen(old));
+        let bytes = chars
+            .into_iter()
+            .map(|c| u8::try_from(c).expect("byte patch holds a char above U+00FF"))
+            .collect();
+        (bytes, results)
+    }
+
+    /// `patch_to_text` for byte patches: the same format, with every byte
+    /// above ASCII escaped as `%XX`.
+    ///
+    /// Args:
+    /// patches: Byte patches.
+    ///
+    /// Returns:
+    /// Text representation of patches.
+    pub fn patch_to_text_bytes(&self, patches: &[Patch]) -> String {
+        let mut text = String::new();
+        for patch in patches {
+            crate::patch::write_patch(patch, |t| encode_uri_bytes(&narrow(t)), &mut text)
+                .expect("writing to a String cannot fail");
+        }
+        text
+    }
+
+    /// Parse byte patch text (`patch_to_text_bytes`); escapes decode to raw
+    /// bytes rather than UTF-8.
+    ///
+    /// Args:
+    /// text: Text representation of patches.
+    ///
+    /// Returns:
+    /// Vector of byte patches, or the first parse error.
+    pub fn patch_from_text_bytes(&self, text: &str) -> Result<Vec<Patch>, ParseError> {
+        crate::patch::parse_patch_text(text, |body| {
+            Some(widen(&percent_decode(body.as_bytes()).collect::<Vec<u8>>()))
+        })
+    }
+}
+
+/// One char per byte, of the same value.
+fn widen(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Inverse of `widen`.
+fn narrow(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| u8::try_from(c).expect("byte patch holds a char above U+00FF"))
+        .collect()
+}
@@ -4,7 +4,7 @@
 use crate::engine;
 #[cfg(feature = "grapheme")]
 use crate::types::Segmentation;
-use crate::types::{max, Diff, Dmp, TDiff};
+use crate::types::{max, Diff, DiffChunk, Dmp, Operation, TDiff};
 
 // The historic public API takes &Vec/&mut Vec/&String; frozen by the
 // drop-in compatibility contract.
@@ -15,7 +15,7 @@ impl Dmp {
     ///
     /// Args:
     /// diffs: Vectors of diff object.
-    pub(crate) fn diff_cleanup_semantic_impl(&mut self, diffs: &mut Vec<TDiff>) {
+    pub(crate) fn diff_cleanup_semantic_impl(&self, diffs: &mut Vec<TDiff>) {
         let mut changes = false;
         let mut equalities: Vec<i32> = vec![]; // Stack of indices where equalities are found.
         let mut last_equality: Vec<char> = vec![]; // Always equal to diffs[equalities[-1]][1]
@@ -27,7 +27,7 @@ impl Dmp {
         let mut length_insertions2 = 0;
         let mut length_deletions2 = 0;
         while (pointer as usize) < diffs.len() {
-            if diffs[pointer as usize].operation == 0 {
+            if diffs[pointer as usize].operation == Operation::Equal {
                 // Equality found.
                 equalities.push(pointer);
                 length_insertions1 = length_insertions2;
@@ -37,7 +37,7 @@ impl Dmp {
                 last_equality = diffs[pointer as usize].data.clone();
             } else {
                 // An insertion or deletion.
-                if diffs[pointer as usize].operation == 1 {
+                if diffs[pointer as usize].operation == Operation::Insert {
                     length_insertions2 += diffs[pointer as usize].data.len() as i32;
                 } else {
                     length_deletions2 += diffs[pointer as usize].data.len() as i32;
@@ -52,11 +52,11 @@ impl Dmp {
                     // Duplicate record.
                     diffs.insert(
                         equalities[equalities.len() - 1] as usize,
-                        TDiff::new(-1, last_equality.clone()),
+                        TDiff::new(Operation::Delete, last_equality.clone()),
                     );
                     // Change second copy to insert.
                     diffs[equalities[equalities.len() - 1] as usize + 1] = TDiff::new(
-                        1,
+                        Operation::Insert,
                         diffs[equalities[equalities.len() - 1] as usize + 1]
                             .data
                             .clone(),
@@ -93,7 +93,8 @@ impl Dmp {
         let mut overlap_length2: i32;
         pointer = 1;
         while (pointer as usize) < diffs.len() {
-            if diffs[pointer as usize - 1].operation == -1 && diffs[pointer as usize].operation == 1
+            if diffs[pointer as usize - 1].operation == Operation::Delete
+                && diffs[pointer as usize].operation == Operation::Insert
             {
                 let deletion_vec: Vec<char> = diffs[pointer as usize - 1].data.clone();
                 let insertion_vec: Vec<char> = diffs[pointer as usize].data.clone();
@@ -106,15 +107,20 @@ impl Dmp {
                         // Overlap found.  Insert an equality and trim the surrounding edits.
                         diffs.insert(
                             pointer as usize,
-                            TDiff::new(0, insertion_vec[..(overlap_length1 as usize)].to_vec()),
+                            TDiff::new(
+                                Operation::Equal,
+                                insertion_vec[..(overlap_length1 as usize)].to_vec(),
+                            ),
                         );
                         diffs[pointer as usize - 1] = TDiff::new(
-                            -1,
+                            Operation::Delete,
                             deletion_vec[..(deletion_vec.len() - overlap_length1 as usize)]
                                 .to_vec(),
                         );
-                        diffs[pointer as usize + 1] =
-                            TDiff::new(1, insertion_vec[(overlap_length1 as usize)..].to_vec());
+                        diffs[pointer as usize + 1] = TDiff::new(
+                            Operation::Insert,
+                            insertion_vec[(overlap_length1 as usize)..].to_vec(),
+                        );
                         pointer += 1;
                     }
                 } else if (overlap_length2 as f32) >= (deletion_vec.len() as f32 / 2.0)
@@ -124,15 +130,20 @@ impl Dmp {
                     // Insert an equality and swap and trim the surrounding edits.
                     diffs.insert(
                         pointer as usize,
-                        TDiff::new(0, deletion_vec[..(overlap_length2 as usize)].to_vec()),
+                        TDiff::new(
+                            Operation::Equal,
+                            deletion_vec[..(overlap_length2 as usize)].to_vec(),
+                        ),
                     );
                     let insertion_vec_len = insertion_vec.len();
                     diffs[pointer as usize - 1] = TDiff::new(
-                        1,
+                        Operation::Insert,
                         insertion_vec[..(insertion_vec_len - overlap_length2 as usize)].to_vec(),
                     );
-                    diffs[pointer as usize + 1] =
-                        TDiff::new(-1, deletion_vec[(overlap_length2 as usize)..].to_vec());
+                    diffs[pointer as usize + 1] = TDiff::new(
+                        Operation::Delete,
+                        deletion_vec[(overlap_length2 as usize)..].to_vec(),
+                    );
                     pointer += 1;
                 }
                 pointer += 1;
@@ -147,12 +158,12 @@ impl Dmp {
     ///
     /// Args:
     /// diffs: Vector of diff object.
-    pub(crate) fn diff_cleanup_semantic_lossless_impl(&mut self, diffs: &mut Vec<TDiff>) {
+    pub(crate) fn diff_cleanup_semantic_lossless_impl(&self, diffs: &mut Vec<TDiff>) {
         let mut pointer: i32 = 1;
         //Intentionally ignore the first and last element (don't need checking).
         while pointer < diffs.len() as i32 - 1 {
-            if diffs[pointer as usize - 1].operation == 0
-                && diffs[pointer as usize + 1].operation == 0
+            if diffs[pointer as usize - 1].operation == Operation::Equal
+                && diffs[pointer as usize + 1].operation == Operation::Equal
             {
                 // This is a single edit surrounded by equalities. Slide it
                 // over one concatenated buffer: the buffer is invariant under
@@ -221,7 +232,7 @@ impl Dmp {
         }
     }
 
-    fn diff_cleanup_semantic_score(&mut self, one: &[char], two: &[char]) -> i32 {
+    fn diff_cleanup_semantic_score(&self, one: &[char], two: &[char]) -> i32 {
         /*
         Given two strings, compute a score representing whether the
         internal boundary falls on logical boundaries.
@@ -305,7 +316,7 @@ impl Dmp {
     ///
     /// Args:
     /// diffs: Vector of diff object.
-    pub(crate) fn diff_cleanup_efficiency_impl(&mut self, diffs: &mut Vec<TDiff>) {
+    pub(crate) fn diff_cleanup_efficiency_impl(&self, diffs: &mut Vec<TDiff>) {
         if diffs.is_empty() {
             return;
         }
@@ -318,7 +329,7 @@ impl Dmp {
         let mut post_ins = false; // Is there an insertion operation after the last equality.
         let mut post_del = false; // Is there a deletion operation after the last equality.
         while (pointer as usize) < diffs.len() {
-            if diffs[pointer as usize].operation == 0 {
+            if diffs[pointer as usize].operation == Operation::Equal {
                 if diffs[pointer as usize].data.len() < self.edit_cost as usize
                     && (post_del || post_ins)
                 {
@@ -336,7 +347,7 @@ impl Dmp {
                 post_del = false;
             } else {
                 // An insertion or deletion.
-                if diffs[pointer as usize].operation == -1 {
+                if diffs[pointer as usize].operation == Operation::Delete {
                     post_del = true;
                 } else {
                     post_ins = true;
@@ -363,11 +374,11 @@ impl Dmp {
                     // Duplicate record.
                     diffs.insert(
                         equalities[equalities.len() - 1] as usize,
-                        TDiff::new(-1, last_equality),
+                        TDiff::new(Operation::Delete, last_equality),
                     );
                     // Change second copy to insert.
                     diffs[equalities[equalities.len() - 1] as usize + 1] = TDiff::new(
-                        1,
+                        Operation::Insert,
                         diffs[equalities[equalities.len() - 1] as usize + 1]
                             .data
                             .clone(),
@@ -406,7 +417,7 @@ impl Dmp {
     ///
     /// Args:
     /// diffs: vectors of diff object.
-    pub(crate) fn diff_cleanup_merge_impl(&mut self, diffs: &mut Vec<TDiff>) {
+    pub(crate) fn diff_cleanup_merge_impl(&self, diffs: &mut Vec<TDiff>) {
         if diffs.is_empty() {
             return;
         }
@@ -423,12 +434,12 @@ impl Dmp {
         let mut count_delete = 0;
         for diff in diffs
             .drain(..)
-            .chain(std::iter::once(TDiff::new(0, vec![])))
+            .chain(std::iter::once(TDiff::new(Operation::Equal, vec![])))
         {
-            if diff.operation == -1 {
+            if diff.operation == Operation::Delete {
                 text_delete.extend_from_slice(&diff.data);
                 count_delete += 1;
-            } else if diff.operation == 1 {
+            } else if diff.operation == Operation::Insert {
                 text_insert.extend_from_slice(&diff.data);
                 count_insert += 1;
             } else {
@@ -443,12 +454,12 @@ impl Dmp {
                         if commonlength != 0 {
                             let prefix = insert_vec[..commonlength].to_vec();
                             match out.last_mut() {
-                                Some(prev) if prev.operation == 0 => {
+                                Some(prev) if prev.operation == Operation::Equal => {
                                     prev.data.extend_from_slice(&prefix)
                                 }
                                 // No equality before the run to grow: mirrors
                                 // the in-place version's insert at the head.
-                                _ => out.insert(0, TDiff::new(0, prefix)),
+                                _ => out.insert(0, TDiff::new(Operation::Equal, prefix)),
                             }
                             insert_vec.drain(..commonlength);
                             delete_vec.drain(..commonlength);
@@ -466,25 +477,31 @@ impl Dmp {
                     }
                     // Add the merged records.
                     if !delete_vec.is_empty() {
-                        out.push(TDiff::new(-1, delete_vec));
+                        out.push(TDiff::new(Operation::Delete, delete_vec));
                     }
                     if !insert_vec.is_empty() {
-                        out.push(TDiff::new(1, insert_vec));
+                        out.push(TDiff::new(Operation::Insert, insert_vec));
                     }
                     out.push(equality);
                 } else if count_delete + count_insert == 1 {
                     // A single edit passes through untouched (even an
                     // empty-text one, as the in-place version left it).
                     if count_delete == 1 {
-                        out.push(TDiff::new(-1, std::mem::take(&mut text_delete)));
+                        out.push(TDiff::new(
+                            Operation::Delete,
+                            std::mem::take(&mut text_delete),
+                        ));
                     } else {
-                        out.push(TDiff::new(1, std::mem::take(&mut text_insert)));
+                        out.push(TDiff::new(
+                            Operation::Insert,
+                            std::mem::take(&mut text_insert),
+                        ));
                     }
                     out.push(equality);
                 } else {
                     // No pending edits: merge adjacent equalities.
                     match out.last_mut() {
-                        Some(prev) if prev.operation == 0 => {
+                        Some(prev) if prev.operation == Operation::Equal => {
                             prev.data.extend_from_slice(&equality.data)
                         }
                         _ => out.push(equality),
@@ -511,7 +528,9 @@ impl Dmp {
         let mut i: i32 = 1;
         // Intentionally ignore the first and last element (don't need checking).
         while (i as usize) < diffs.len() - 1 {
-            if diffs[i as usize - 1].operation == 0 && diffs[i as usize + 1].operation == 0 {
+            if diffs[i as usize - 1].operation == Operation::Equal
+                && diffs[i as usize + 1].operation == Operation::Equal
+            {
                 // This is a single edit surrounded by equalities.
                 if diffs[i as usize]
                     .data
@@ -561,19 +580,19 @@ impl Dmp {
     ///
     /// Returns:
     /// Location within text2.
-    pub fn diff_xindex(&mut self, diffs: &Vec<Diff>, loc: i32) -> i32 {
+    pub fn diff_xindex(&self, diffs: &Vec<Diff>, loc: i32) -> i32 {
         let mut chars1 = 0;
         let mut chars2 = 0;
         let mut last_chars1 = 0;
         let mut last_chars2 = 0;
-        let mut lastdiff = Diff::new(0, "".to_string());
+        let mut lastdiff = Diff::new(Operation::Equal, "".to_string());
         let z = 0;
         for diffs_item in diffs {
-            if diffs_item.operation != 1 {
+            if diffs_item.operation != Operation::Insert {
                 // Equality or deletion.
                 chars1 += diffs_item.text.chars().count() as i32;
             }
-            if diffs_item.operation != -1 {
+            if diffs_item.operation != Operation::Delete {
                 // Equality or insertion.
                 chars2 += diffs_item.text.chars().count() as i32;
             }
@@ -585,7 +604,7 @@ impl Dmp {
             last_chars1 = chars1;
             last_chars2 = chars2;
         }
-        if lastdiff.operation == -1 && diffs.len() != z {
+        if lastdiff.operation == Operation::Delete && diffs.len() != z {
             // The location was deleted.
             return last_chars2;
         }
@@ -600,11 +619,11 @@ impl Dmp {
     ///
     /// Returns:
     /// Source text.
-    pub fn diff_text1(&mut self, diffs: &mut Vec<Diff>) -> String {
+    pub fn diff_text1<D: DiffChunk>(&self, diffs: &mut Vec<D>) -> String {
         let mut text: String = "".to_string();
         for adiff in diffs {
-            if adiff.operation != 1 {
-                text += adiff.text.as_str();
+            if adiff.operation() != Operation::Insert {
+                text += adiff.text();
             }
         }
         text
@@ -617,11 +636,11 @@ impl Dmp {
     ///
     /// Returns:
     /// destination text.
-    pub fn diff_text2(&mut self, diffs: &mut Vec<Diff>) -> String {
+    pub fn diff_text2<D: DiffChunk>(&self, diffs: &mut Vec<D>) -> String {
         let mut text: String = "".to_string();
         for adiff in diffs {
-            if adiff.operation != -1 {
-                text += adiff.text.as_str();
+            if adiff.operation() != Operation::Delete {
+                text += adiff.text();
             }
         }
         text
@@ -635,15 +654,15 @@ impl Dmp {
     ///
     /// Returns:
     /// Number of changes.
-    pub fn diff_levenshtein(&mut self, diffs: &Vec<Diff>) -> i32 {
+    pub fn diff_levenshtein<D: DiffChunk>(&self, diffs: &Vec<D>) -> i32 {
         let mut levenshtein = 0;
         let mut insertions = 0;
         let mut deletions = 0;
         for adiff in diffs {
-            if adiff.operation == 1 {
-                insertions += adiff.text.chars().count();
-            } else if adiff.operation == -1 {
-                deletions += adiff.text.chars().count();
+            if adiff.operation() == Operation::Insert {
+                insertions += adiff.text().chars().count();
+            } else if adiff.operation() == Operation::Delete {
+                deletions += adiff.text().chars().count();
             } else {
                 // A deletion and an insertion is one substitution.
                 levenshtein += max(insertions as i32, deletions as i32);
@@ -667,19 +686,19 @@ impl Dmp {
 // drop-in compatibility contract.
 #[allow(clippy::ptr_arg)]
 impl Dmp {
-    pub fn diff_cleanup_semantic(&mut self, diffs: &mut Vec<Diff>) {
+    pub fn diff_cleanup_semantic(&self, diffs: &mut Vec<Diff>) {
         dispatch(self, diffs, Dmp::diff_cleanup_semantic_impl);
     }
 
-    pub fn diff_cleanup_semantic_lossless(&mut self, diffs: &mut Vec<Diff>) {
+    pub fn diff_cleanup_semantic_lossless(&self, diffs: &mut Vec<Diff>) {
         dispatch(self, diffs, Dmp::diff_cleanup_semantic_lossless_impl);
     }
 
-    pub fn diff_cleanup_efficiency(&mut self, diffs: &mut Vec<Diff>) {
+    pub fn diff_cleanup_efficiency(&self, diffs: &mut Vec<Diff>) {
         dispatch(self, diffs, Dmp::diff_cleanup_efficiency_impl);
     }
 
-    pub fn diff_cleanup_merge(&mut self, diffs: &mut Vec<Diff>) {
+    pub fn diff_cleanup_merge(&self, diffs: &mut Vec<Diff>) {
         dispatch(self, diffs, Dmp::diff_cleanup_merge_impl);
     }
 }
@@ -689,7 +708,7 @@ impl Dmp {
 /// callers pay one round-trip instead of the per-operation decodes the passes
 /// used to do internally. Grapheme mode packs into cluster-id space first so
 /// no pass can split a cluster.
-fn dispatch(dmp: &mut Dmp, diffs: &mut Vec<Diff>, pass: fn(&mut Dmp, &mut Vec<TDiff>)) {
+fn dispatch(dmp: &Dmp, diffs: &mut Vec<Diff>, pass: fn(&Dmp, &mut Vec<TDiff>)) {
     #[cfg(feature = "grapheme")]
     {
         if dmp.segmentation == Segmentation::Grapheme {
@@ -0,0 +1,312 @@
+// Composition of sequential edits: diffs (or patches) taking A to B and B to
+// C become one list taking A to C, so a version chain can be collapsed
+// without materializing the texts in between.
+
+use crate::types::{Diff, Dmp, Operation, Patch};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Why two edit lists could not be composed.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ComposeError {
+    /// The second list does not start from the text the first produces:
+    /// the two disagree, or one ends before the other, `offset` chars into
+    /// that text.
+    Mismatch { offset: usize },
+    /// A patch changes text the patch before it already covers. `index` is
+    /// its position in the first list, or in the second if `second` is set.
+    Overlap { second: bool, index: usize },
+}
+
+impl fmt::Display for ComposeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ComposeError::Mismatch { offset } => write!(
+                f,
+                "Second edit does not start from the first one's new text at char {}",
+                offset
+            ),
+            ComposeError::Overlap { second, index } => write!(
+                f,
+                "Patch {} of the {} list changes text the patch before it covers",
+                index,
+                if *second { "second" } else { "first" }
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ComposeError {}
+
+/// A stretch of an edit script being composed: text under an operation, or
+/// an equality whose text is not known (the text between two patches).
+enum Piece {
+    Text(Operation, Vec<char>),
+    Gap(usize),
+}
+
+impl Piece {
+    /// Length in chars. For the pieces that cover the middle text (all but
+    /// the first script's deletions and the second's insertions), how much
+    /// of it they cover.
+    fn len(&self) -> usize {
+        match self {
+            Piece::Text(_, text) => text.len(),
+            Piece::Gap(len) => *len,
+        }
+    }
+
+    /// The first `n` chars, and the rest if any.
+    fn split(self, n: usize) -> (Piece, Option<Piece>) {
+        match self {
+            Piece::Text(op, mut text) => {
+                let rest = text.split_off(n);
+                let rest = (!rest.is_empty()).then_some(Piece::Text(op, rest));
+                (Piece::Text(op, text), rest)
+            }
+            Piece::Gap(len) => (Piece::Gap(n), (len > n).then_some(Piece::Gap(len - n))),
+        }
+    }
+}
+
+impl Dmp {
+    /// Compose two diffs: `a` taking text A to B and `b` taking B to C
+    /// become one diff taking A to C. Text `a` inserts and `b` deletes
+    /// cancels out; the result is cleaned with `diff_cleanup_merge`.
+    ///
+    /// Args:
+    /// a: Vector of diffs from A to B.
+    /// b: Vector of diffs from B to C.
+    ///
+    /// Returns:
+    /// Vector of diffs from A to C, or `ComposeError::Mismatch` if `b`
+    /// does not start from the text `a` produces.
+    pub fn diff_compose(&self, a: &[Diff], b: &[Diff]) -> Result<Vec<Diff>, ComposeError> {
+        let pieces = |diffs: &[Diff]| -> Vec<Piece> {
+            diffs
+                .iter()
+                .map(|d| Piece::Text(d.operation, d.text.chars().collect()))
+                .collect()
+        };
+        // Gaps only come from patch lists.
+        let mut diffs: Vec<Diff> = compose(pieces(a), pieces(b), false)?
+            .into_iter()
+            .filter_map(|piece| match piece {
+                Piece::Text(op, text) => Some(Diff::new(op, text.into_iter().collect())),
+                Piece::Gap(_) => None,
+            })
+            .collect();
+        self.merge(&mut diffs);
+        Ok(diffs)
+    }
+
+    /// Compose two patch lists: `a` taking text A to B and `b` taking B to
+    /// C become one list taking A to C, with no need for B. Where the
+    /// patches overlap their edits are composed as in `diff_compose`;
+    /// patches whose changes cancel out are dropped, and an equality longer
+    /// than twice `patch_margin` splits a patch in two, as `patch_make`
+    /// would.
+    ///
+    /// Args:
+    /// a: Vector of Patch objects from A to B.
+    /// b: Vector of Patch objects from B to C.
+    ///
+    /// Returns:
+    /// Vector of Patch objects from A to C, or `ComposeError::Overlap` if a
+    /// patch changes text an earlier patch of its list already covers, or
+    /// `ComposeError::Mismatch` if `b` disagrees with the text `a` produces
+    /// where both know it.
+    pub fn patch_compose(&self, a: &[Patch], b: &[Patch]) -> Result<Vec<Patch>, ComposeError> {
+        let margin = self.patch_margin.max(0) as usize;
+        let mut patches: Vec<Patch> = vec![];
+        // Runs of known text between gaps. Like `patch_make`, each patch is
+        // placed in the text the patches before it produce, so only the
+        // position in C is tracked.
+        let mut run: Vec<Diff> = vec![];
+        let mut start = 0;
+        let mut pos = 0;
+        let pieces = compose(patch_pieces(a, false)?, patch_pieces(b, true)?, true)?;
+        for piece in pieces {
+            match piece {
+                Piece::Gap(len) => {
+                    self.close_run(&mut run, start, margin, &mut patches);
+                    pos += len;
+                    start = pos;
+                }
+                Piece::Text(op, text) => {
+                    if op != Operation::Delete {
+                        pos += text.len();
+                    }
+                    run.push(Diff::new(op, text.into_iter().collect()));
+                }
+            }
+        }
+        self.close_run(&mut run, start, margin, &mut patches);
+        Ok(patches)
+    }
+
+    /// `diff_cleanup_merge`, run again when the first pass turned text one
+    /// side deleted and the other put back into an equality beside another.
+    fn merge(&self, diffs: &mut Vec<Diff>) {
+        self.diff_cleanup_merge(diffs);
+        if diffs.windows(2).any(|w| w[0].operation == w[1].operation) {
+            self.diff_cleanup_merge(diffs);
+        }
+    }
+
+    /// Turn a run of composed diffs starting at `start` into patches,
+    /// splitting it at long equalities.
+    fn close_run(
+        &self,
+        run: &mut Vec<Diff>,
+        start: usize,
+        margin: usize,
+        patches: &mut Vec<Patch>,
+    ) {
+        self.merge(run);
+        let last = run.len().saturating_sub(1);
+        let mut patch = Patch::new(vec![], start as i32, start as i32, 0, 0);
+        for (i, diff) in run.drain(..).enumerate() {
+            let len = diff.text.chars().count();
+            if diff.operation == Operation::Equal && 0 < i && i < last && len > 2 * margin {
+                let text: Vec<char> = diff.text.chars().collect();
+                push_diff(
+                    &mut patch,
+                    Diff::new(Operation::Equal, text[..margin].iter().collect()),
+                );
+                let next_start = patch.start2 + patch.length2 + (len - 2 * margin) as i32;
+                let next = Patch::new(vec![], next_start, next_start, 0, 0);
+                push_changed(std::mem::replace(&mut patch, next), patches);
+                push_diff(
+                    &mut patch,
+                    Diff::new(Operation::Equal, text[len - margin..].iter().collect()),
+                );
+            } else {
+                push_diff(&mut patch, diff);
+            }
+        }
+        push_changed(patch, patches);
+    }
+}
+
+/// Compose two edit scripts over their shared middle text. With `open`,
+/// the scripts are sparse (patches): text past the end of either is an
+/// unknown equality, so the other's remaining pieces pass through.
+fn compose(a: Vec<Piece>, b: Vec<Piece>, open: bool) -> Result<Vec<Piece>, ComposeError> {
+    let nonempty = |pieces: Vec<Piece>| -> VecDeque<Piece> {
+        pieces.into_iter().filter(|p| p.len() > 0).collect()
+    };
+    let mut a = nonempty(a);
+    let mut b = nonempty(b);
+    let mut out: Vec<Piece> = vec![];
+    // How far into the middle text both scripts have got.
+    let mut offset = 0;
+    loop {
+        // The first script's deletions and the second's insertions never
+        // touch the middle text.
+        if let Some(Piece::Text(Operation::Delete, _)) = a.front() {
+            out.extend(a.pop_front());
+            continue;
+        }
+        if let Some(Piece::Text(Operation::Insert, _)) = b.front() {
+            out.extend(b.pop_front());
+            continue;
+        }
+        let (pa, pb) = match (a.pop_front(), b.pop_front()) {
+            (None, None) => break,
+            (Some(pa), Some(pb)) => (pa, pb),
+            (Some(piece), None) | (None, Some(piece)) if open => {
+                out.push(piece);
+                continue;
+            }
+            _ => return Err(ComposeError::Mismatch { offset }),
+        };
+        let n = usize::min(pa.len(), pb.len());
+        let (pa, rest) = pa.split(n);
+        if let Some(rest) = rest {
+            a.push_front(rest);
+        }
+        let (pb, rest) = pb.split(n);
+        if let Some(rest) = rest {
+            b.push_front(rest);
+        }
+        let at = offset;
+        offset += n;
+        let piece = match (pa, pb) {
+            (Piece::Gap(len), Piece::Gap(_)) => Piece::Gap(len),
+            // Equal or Delete of middle text the first script kept.
+            (Piece::Gap(_), known) => known,
+            // Equal or Insert of middle text the second script keeps.
+            (known, Piece::Gap(_)) => known,
+            (Piece::Text(op_a, text_a), Piece::Text(op_b, text_b)) => {
+                if text_a != text_b {
+                    let same = text_a.iter().zip(&text_b).take_while(|(x, y)| x == y);
+                    return Err(ComposeError::Mismatch {
+                        offset: at + same.count(),
+                    });
+                }
+                match (op_a, op_b) {
+                    (Operation::Insert, Operation::Delete) => continue,
+                    (Operation::Insert, _) => Piece::Text(Operation::Insert, text_a),
+                    (_, Operation::Delete) => Piece::Text(Operation::Delete, text_a),
+                    _ => Piece::Text(Operation::Equal, text_a),
+                }
+            }
+        };
+        out.push(piece);
+    }
+    Ok(out)
+}
+
+/// A patch list as pieces over its old text, with gaps between the patches.
+/// Each patch's `start1` counts from the start of the text the patches
+/// before it produce; its leading context may overlap the previous patch's
+/// trailing context, and is then dropped as already covered. `second` says
+/// which list this is, for errors.
+fn patch_pieces(patches: &[Patch], second: bool) -> Result<Vec<Piece>, ComposeError> {
+    let mut pieces: Vec<Piece> = vec![];
+    // Where the previous patch's new text ends.
+    let mut end: i32 = 0;
+    for (index, patch) in patches.iter().enumerate() {
+        pieces.push(Piece::Gap(i32::max(patch.start1 - end, 0) as usize));
+        let mut overlap = usize::try_from(end - patch.start1).unwrap_or(0);
+        for diff in &patch.diffs {
+            let piece = Piece::Text(diff.operation, diff.text.chars().collect());
+            if overlap == 0 || piece.len() == 0 {
+                pieces.push(piece);
+                continue;
+            }
+            if diff.operation != Operation::Equal {
+                return Err(ComposeError::Overlap { second, index });
+            }
+            let n = usize::min(overlap, piece.len());
+            overlap -= n;
+            pieces.extend(piece.split(n).1);
+        }
+        end = patch.start2 + patch.length2;
+    }
+    Ok(pieces)
+}
+
+/// Append a diff to a patch, growing its lengths.
+fn push_diff(patch: &mut Patch, diff: Diff) {
+    if diff.text.is_empty() {
+        return;
+    }
+    let len = diff.text.chars().count() as i32;
+    if diff.operation != Operation::Insert {
+        patch.length1 += len;
+    }
+    if diff.operation != Operation::Delete {
+        patch.length2 += len;
+    }
+    patch.diffs.push(diff);
+}
+
+/// Keep `patch` unless it is all context.
+fn push_changed(patch: Patch, patches: &mut Vec<Patch>) {
+    if patch.diffs.iter().any(|d| d.operation != Operation::Equal) {
+        patches.push(patch);
+    }
+}
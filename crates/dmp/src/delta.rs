@@ -2,9 +2,11 @@
 // lengths in Unicode scalars, plus the encodeURI-style escaping shared with
 // the patch text format.
 
-use crate::types::{Diff, Dmp};
-use percent_encoding::{percent_decode, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
-use std::fmt;
+use crate::error::ParseError;
+use crate::types::{Diff, DiffChunk, Dmp, Operation};
+use percent_encoding::{
+    percent_decode, percent_encode, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC,
+};
 
 /// Characters JavaScript's `encodeURI` leaves unescaped (beyond
 /// alphanumerics), plus space: both wire formats post-process `%20` back to a
@@ -37,13 +39,10 @@ pub(crate) fn encode_uri(text: &str) -> String {
     utf8_percent_encode(text, ENCODE_URI_SET).collect()
 }
 
-#[derive(Debug)]
-pub(crate) struct DeltaError(String);
-
-impl fmt::Display for DeltaError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
-    }
+/// `encode_uri` over raw bytes: the same escapes for ASCII, and `%XX` for
+/// every byte above it (no UTF-8 interpretation).
+pub(crate) fn encode_uri_bytes(bytes: &[u8]) -> String {
+    percent_encode(bytes, ENCODE_URI_SET).collect()
 }
 
 // The historic public API takes &Vec/&mut Vec/&String; frozen by the
@@ -60,13 +59,13 @@ impl Dmp {
     ///
     /// Returns:
     /// Delta text.
-    pub fn diff_todelta(&mut self, diffs: &mut Vec<Diff>) -> String {
+    pub fn diff_todelta<D: DiffChunk>(&self, diffs: &mut Vec<D>) -> String {
         let tokens: Vec<String> = diffs
             .iter()
-            .map(|diff| match diff.operation {
-                1 => format!("+{}", encode_uri(&diff.text)),
-                -1 => format!("-{}", diff.text.chars().count()),
-                _ => format!("={}", diff.text.chars().count()),
+            .map(|diff| match diff.operation() {
+                Operation::Insert => format!("+{}", encode_uri(diff.text())),
+                Operation::Delete => format!("-{}", diff.text().chars().count()),
+                Operation::Equal => format!("={}", diff.text().chars().count()),
             })
             .collect();
         tokens.join("\t")
@@ -83,17 +82,45 @@ impl Dmp {
     /// Vector of diff object.
     ///
     /// Panics on invalid input (malformed escape, bad length, or a delta that
-    /// does not consume text1 exactly).
-    pub fn diff_from_delta(&mut self, text1: &str, delta: &str) -> Vec<Diff> {
+    /// does not consume text1 exactly); see `try_diff_from_delta`.
+    pub fn diff_from_delta(&self, text1: &str, delta: &str) -> Vec<Diff> {
         try_from_delta(text1, delta).unwrap_or_else(|e| panic!("{}", e))
     }
+
+    /// `diff_from_delta` that reports malformed input as a `ParseError`
+    /// (with the offending token's index) instead of panicking.
+    pub fn try_diff_from_delta(&self, text1: &str, delta: &str) -> Result<Vec<Diff>, ParseError> {
+        try_from_delta(text1, delta)
+    }
 }
 
-pub(crate) fn try_from_delta(text1: &str, delta: &str) -> Result<Vec<Diff>, DeltaError> {
+pub(crate) fn try_from_delta(text1: &str, delta: &str) -> Result<Vec<Diff>, ParseError> {
     let chars: Vec<char> = text1.chars().collect();
-    let mut diffs: Vec<Diff> = vec![];
+    let runs = parse_delta(&chars, delta, |param| {
+        percent_decode(param.as_bytes())
+            .decode_utf8()
+            .ok()
+            .map(|text| text.chars().collect())
+    })?;
+    Ok(runs
+        .into_iter()
+        .map(|(op, run)| Diff::new(op, run.into_iter().collect()))
+        .collect())
+}
+
+/// The delta decoder over any token source (chars for text, bytes for byte
+/// diffs), with the insert escape decoding supplied by the caller; `None`
+/// from `decode` is an illegal escape. Lengths count `source` tokens.
+pub(crate) fn parse_delta<T: Copy>(
+    source: &[T],
+    delta: &str,
+    decode: impl Fn(&str) -> Option<Vec<T>>,
+) -> Result<Vec<(Operation, Vec<T>)>, ParseError> {
+    let mut runs: Vec<(Operation, Vec<T>)> = vec![];
     let mut pointer = 0usize;
-    for token in delta.split('\t') {
+    let mut tokens = 0;
+    for (index, token) in delta.split('\t').enumerate() {
+        tokens = index + 1;
         if token.is_empty() {
             // Blank tokens are ok (from a trailing \t).
             continue;
@@ -105,42 +132,47 @@ pub(crate) fn try_from_delta(text1: &str, delta: &str) -> Result<Vec<Diff>, Delt
         let param = token_chars.as_str();
         match op {
             '+' => {
-                let text = percent_decode(param.as_bytes())
-                    .decode_utf8()
-                    .map_err(|_| {
-                        DeltaError(format!("Illegal escape in diff_from_delta: {}", param))
-                    })?;
-                diffs.push(Diff::new(1, text.to_string()));
+                let run = decode(param).ok_or_else(|| ParseError::IllegalEscape {
+                    index,
+                    text: param.to_string(),
+                })?;
+                runs.push((Operation::Insert, run));
             }
             '-' | '=' => {
-                let n: usize = param.parse().map_err(|_| {
-                    DeltaError(format!("Invalid number in diff_from_delta: {}", param))
+                let n: usize = param.parse().map_err(|_| ParseError::InvalidLength {
+                    index,
+                    text: param.to_string(),
                 })?;
-                if pointer + n > chars.len() {
-                    return Err(DeltaError(format!(
-                        "Delta length ({}) larger than source text length ({})",
-                        pointer + n,
-                        chars.len()
-                    )));
+                if pointer + n > source.len() {
+                    return Err(ParseError::LengthMismatch {
+                        index,
+                        delta_length: pointer + n,
+                        text_length: source.len(),
+                    });
                 }
-                let text: String = chars[pointer..pointer + n].iter().collect();
+                let run = source[pointer..pointer + n].to_vec();
                 pointer += n;
-                diffs.push(Diff::new(if op == '=' { 0 } else { -1 }, text));
+                let op = if op == '=' {
+                    Operation::Equal
+                } else {
+                    Operation::Delete
+                };
+                runs.push((op, run));
             }
             _ => {
-                return Err(DeltaError(format!(
-                    "Invalid diff operation in diff_from_delta: {}",
-                    token
-                )))
+                return Err(ParseError::InvalidOperation {
+                    index,
+                    text: token.to_string(),
+                })
             }
         }
     }
-    if pointer != chars.len() {
-        return Err(DeltaError(format!(
-            "Delta length ({}) does not equal source text length ({})",
-            pointer,
-            chars.len()
-        )));
+    if pointer != source.len() {
+        return Err(ParseError::LengthMismatch {
+            index: tokens,
+            delta_length: pointer,
+            text_length: source.len(),
+        });
     }
-    Ok(diffs)
+    Ok(runs)
 }
@@ -3,7 +3,8 @@
 // primitives live in engine.rs; text materialization happens here.
 
 use crate::engine;
-use crate::types::{Diff, DiffToken, Dmp, TDiff};
+use crate::outcome::Limits;
+use crate::types::{Diff, DiffAlgorithm, DiffRef, DiffToken, Dmp, Operation, TDiff};
 use std::time::{Duration, Instant};
 
 // The historic public API takes &Vec/&mut Vec/&String; frozen by the
@@ -21,50 +22,155 @@ impl Dmp {
     /// Defaults to true, which does a faster, slightly less optimal diff.
     /// Returns:
     /// Vector of diffs as changes.
-    pub fn diff_main(&mut self, text1: &str, text2: &str, checklines: bool) -> Vec<Diff> {
-        let deadline = self.deadline_from_now();
+    pub fn diff_main(&self, text1: &str, text2: &str, checklines: bool) -> Vec<Diff> {
+        let limits = Limits::from_dmp(self);
+        diff_main_limited(self, text1, text2, checklines, &limits)
+    }
+
+    /// `diff_main` returning chunks that borrow from the inputs instead of
+    /// owning copies, so the result holds no text of its own. The chunks are
+    /// the ones `diff_main` would return.
+    ///
+    /// Only the result borrows. The engine's pieces are still owned token
+    /// vectors copied out of the inputs, not ranges into `text1`/`text2`, so
+    /// peak memory during the call is as for `diff_main`; none of it
+    /// outlives the call. Threading ranges through the bisect and cleanup
+    /// passes is out of scope here.
+    ///
+    /// Args:
+    /// text1: Old string to be diffed.
+    /// text2: New string to be diffed.
+    /// checklines: As for `diff_main`.
+    ///
+    /// Returns:
+    /// Vector of borrowed diffs as changes.
+    pub fn diff_main_ref<'a>(
+        &self,
+        text1: &'a str,
+        text2: &'a str,
+        checklines: bool,
+    ) -> Vec<DiffRef<'a>> {
         #[cfg(feature = "grapheme")]
         {
             if self.segmentation == crate::types::Segmentation::Grapheme {
-                // The whole pipeline (line packing included) runs on cluster
-                // ids, so every pass stays cluster-atomic; ids never collide
-                // with '\n' because allocation starts above it.
-                let mut packer = crate::tokenize::GraphemePacker::new(&[text1, text2]);
-                let packed1 = packer.pack(text1);
-                let packed2 = packer.pack(text2);
-                let mut scratch = Vec::new();
-                let mut diffs = main_internal(
-                    self,
-                    &packed1,
-                    &packed2,
-                    checklines,
-                    true,
-                    deadline,
-                    &mut scratch,
+                // The cluster packing produces owned pieces; borrow by
+                // their byte lengths.
+                let diffs = self.diff_main(text1, text2, checklines);
+                return materialize_ref(
+                    diffs.iter().map(|d| (d.operation, d.text.len())),
+                    text1,
+                    text2,
                 );
-                packer.unpack_diffs(&mut diffs);
-                return diffs;
             }
         }
-        main_internal(
+        if text1.is_empty() && text2.is_empty() {
+            return vec![];
+        }
+        if text1 == text2 {
+            return vec![DiffRef::new(Operation::Equal, text1)];
+        }
+        let limits = Limits::from_dmp(self);
+        let tokens = diff_str_tokens(
             self,
             text1,
             text2,
             checklines,
             true,
-            deadline,
+            &limits,
             &mut Vec::new(),
+        );
+        materialize_ref(
+            tokens
+                .iter()
+                .map(|t| (t.operation, t.data.iter().map(|c| c.len_utf8()).sum())),
+            text1,
+            text2,
         )
     }
 
+    /// Diff two texts word by word, keeping every token whole: a changed
+    /// word is deleted or inserted entire, never split into chars, so each
+    /// edit carries complete words and values (`v1.2.3`, `$19.99`, URLs) as
+    /// they appear in the text. By default words are maximal runs of
+    /// non-whitespace and each whitespace cluster is a token of its own;
+    /// `word_vocabulary` selects UAX #29 words instead. Either way tokens
+    /// are cut on grapheme cluster boundaries, so `\r\n` and base+combining
+    /// pairs are never split.
+    ///
+    /// No semantic cleanup is applied, so each changed token can be matched
+    /// on its own. Run `diff_cleanup_semantic` on the result for a coarser,
+    /// more readable view (it also tidies reordered words).
+    ///
+    /// Args:
+    /// old: Old string to be diffed.
+    /// new: New string to be diffed.
+    ///
+    /// Returns:
+    /// Vector of diffs as changes.
+    pub fn diff_words(&self, old: &str, new: &str) -> Vec<Diff> {
+        diff_words_limited(self, old, new, &Limits::from_dmp(self))
+    }
+
+    /// The words a diff inserts, split edit by edit so that words either
+    /// side of unchanged text never fuse. Words are cut as `diff_words`
+    /// cuts them (`word_vocabulary`), without the whitespace between them.
+    ///
+    /// Args:
+    /// diffs: Vector of diff objects, typically from `diff_words`.
+    ///
+    /// Returns:
+    /// The inserted words, in order.
+    pub fn added_tokens<'a>(&self, diffs: &'a [Diff]) -> Vec<&'a str> {
+        edit_tokens(self, diffs, Operation::Insert)
+    }
+
+    /// The words a diff deletes; see `added_tokens`.
+    ///
+    /// Args:
+    /// diffs: Vector of diff objects, typically from `diff_words`.
+    ///
+    /// Returns:
+    /// The deleted words, in order.
+    pub fn removed_tokens<'a>(&self, diffs: &'a [Diff]) -> Vec<&'a str> {
+        edit_tokens(self, diffs, Operation::Delete)
+    }
+
+    /// Diff two texts over the tokens of a caller-chosen [`Tokenizer`]
+    /// (sentences, CSV cells, markup tags...). Like `diff_words`, changed
+    /// tokens are deleted or inserted whole and no cleanup is applied.
+    ///
+    /// Args:
+    /// old: Old string to be diffed.
+    /// new: New string to be diffed.
+    /// tokenizer: Splits each text into atomic units.
+    ///
+    /// Returns:
+    /// Vector of diffs as changes.
+    ///
+    /// Panics if the tokenizer's tokens do not concatenate back to the text.
+    pub fn diff_tokens<K: crate::Tokenizer + ?Sized>(
+        &self,
+        old: &str,
+        new: &str,
+        tokenizer: &K,
+    ) -> Vec<Diff> {
+        let limits = Limits::from_dmp(self);
+        let (text1, text2, store) = crate::tokenize::tokens_tochars_arena(old, new, tokenizer);
+        let mut diffs =
+            diff_str_tokens(self, &text1, &text2, false, false, &limits, &mut Vec::new());
+        crate::tokenize::chars_tolines_arena(&mut diffs, &store);
+        materialize(diffs)
+    }
+
     /// The deadline equivalent of `diff_timeout` starting now; bisect gives up
     /// once it passes. `Some(0.0)` therefore means "zero budget", while `None`
-    /// disables the deadline entirely (and with it the half-match speedup).
+    /// disables the deadline entirely (and with it the half-match speedup,
+    /// unless `diff_budget` is set).
     ///
     /// Degenerate values mirror the historical float comparisons instead of
     /// panicking in Duration/Instant math: a negative timeout behaves as zero
-    /// budget, NaN as no deadline, and huge values are capped (~30 years).
-    fn deadline_from_now(&self) -> Option<Instant> {
+    /// budget, NaN as no limits, and huge values are capped (~30 years).
+    pub(crate) fn deadline_from_now(&self) -> Option<Instant> {
         let secs = self.diff_timeout?;
         if secs.is_nan() {
             return None;
@@ -82,7 +188,7 @@ impl Dmp {
     ///
     /// Returns:
     /// Vector of diffs as changes.
-    pub fn diff_linemode(&mut self, text1: &Vec<char>, text2: &Vec<char>) -> Vec<Diff> {
+    pub fn diff_linemode(&self, text1: &Vec<char>, text2: &Vec<char>) -> Vec<Diff> {
         #[cfg(feature = "grapheme")]
         {
             if self.segmentation == crate::types::Segmentation::Grapheme {
@@ -93,8 +199,8 @@ impl Dmp {
                 return self.diff_main(&t1, &t2, true);
             }
         }
-        let deadline = self.deadline_from_now();
-        materialize(line_mode(self, text1, text2, deadline, &mut Vec::new()))
+        let limits = Limits::from_dmp(self);
+        materialize(line_mode(self, text1, text2, &limits, &mut Vec::new()))
     }
 
     /// Find the 'middle snake' of a diff, split the problem in two
@@ -107,29 +213,29 @@ impl Dmp {
     ///
     /// Returns:
     /// Vector of diffs as changes.
-    pub fn diff_bisect(&mut self, char1: &Vec<char>, char2: &Vec<char>) -> Vec<Diff> {
-        let deadline = self.deadline_from_now();
+    pub fn diff_bisect(&self, char1: &Vec<char>, char2: &Vec<char>) -> Vec<Diff> {
+        let limits = Limits::from_dmp(self);
         materialize(bisect_diff(
             self,
             char1,
             char2,
             true,
-            deadline,
+            &limits,
             &mut Vec::new(),
         ))
     }
 
-    pub fn diff_common_prefix(&mut self, text1: &Vec<char>, text2: &Vec<char>) -> i32 {
+    pub fn diff_common_prefix(&self, text1: &Vec<char>, text2: &Vec<char>) -> i32 {
         // The number of characters common to the start of each text.
         engine::common_prefix(text1, text2) as i32
     }
 
-    pub fn diff_common_suffix(&mut self, text1: &Vec<char>, text2: &Vec<char>) -> i32 {
+    pub fn diff_common_suffix(&self, text1: &Vec<char>, text2: &Vec<char>) -> i32 {
         // The number of characters common to the end of each text.
         engine::common_suffix(text1, text2) as i32
     }
 
-    pub fn diff_common_overlap(&mut self, text1: &Vec<char>, text2: &Vec<char>) -> i32 {
+    pub fn diff_common_overlap(&self, text1: &Vec<char>, text2: &Vec<char>) -> i32 {
         // The number of characters common to the end of text1 and the start of text2.
         engine::common_overlap(text1, text2) as i32
     }
@@ -142,12 +248,12 @@ impl Dmp {
     /// Five element Vector, containing the prefix of text1, the suffix of text1,
     /// the prefix of text2, the suffix of text2 and the common middle.  Or empty vector
     /// if there was no match.
-    pub fn diff_half_match(&mut self, text1: &Vec<char>, text2: &Vec<char>) -> Vec<String> {
+    pub fn diff_half_match(&self, text1: &Vec<char>, text2: &Vec<char>) -> Vec<String> {
         // Don't risk returning a non-optimal diff if we have unlimited time.
         // Uses the deadline mapping (not diff_timeout directly) so degenerate
         // values like NaN disable half-match exactly as they disable the
         // deadline in compute().
-        if self.deadline_from_now().is_none() {
+        if self.deadline_from_now().is_none() && self.diff_budget.is_none() {
             return vec![];
         }
         match engine::half_match(text1, text2) {
@@ -183,13 +289,77 @@ fn materialize(tokens: Vec<TDiff>) -> Vec<Diff> {
     tokens.into_iter().map(TDiff::into_diff).collect()
 }
 
+/// The borrowing counterpart of `materialize`: slice each piece, given as
+/// its operation and UTF-8 length, out of the input it came from.
+fn materialize_ref<'a>(
+    pieces: impl Iterator<Item = (Operation, usize)>,
+    text1: &'a str,
+    text2: &'a str,
+) -> Vec<DiffRef<'a>> {
+    let mut pos1 = 0;
+    let mut pos2 = 0;
+    pieces
+        .map(|(op, len)| {
+            let text = match op {
+                Operation::Insert => &text2[pos2..pos2 + len],
+                _ => &text1[pos1..pos1 + len],
+            };
+            if op != Operation::Insert {
+                pos1 += len;
+            }
+            if op != Operation::Delete {
+                pos2 += len;
+            }
+            DiffRef::new(op, text)
+        })
+        .collect()
+}
+
+/// `diff_main` under caller-built limits (cancellation, progress); the
+/// progress total is the byte length of both inputs.
+pub(crate) fn diff_main_limited(
+    dmp: &Dmp,
+    text1: &str,
+    text2: &str,
+    checklines: bool,
+    limits: &Limits,
+) -> Vec<Diff> {
+    #[cfg(feature = "grapheme")]
+    {
+        if dmp.segmentation == crate::types::Segmentation::Grapheme {
+            // The whole pipeline (line packing included) runs on cluster
+            // ids, so every pass stays cluster-atomic; ids never collide
+            // with '\n' because allocation starts above it.
+            let mut packer = crate::tokenize::GraphemePacker::new(&[text1, text2]);
+            let packed1 = packer.pack(text1);
+            let packed2 = packer.pack(text2);
+            limits.record(|s| s.grapheme_packed = true);
+            limits.begin_packed(text1.len() + text2.len(), || packer.widths());
+            let mut scratch = Vec::new();
+            let mut diffs = main_internal(
+                dmp,
+                &packed1,
+                &packed2,
+                checklines,
+                true,
+                limits,
+                &mut scratch,
+            );
+            packer.unpack_diffs(&mut diffs);
+            return diffs;
+        }
+    }
+    limits.begin(text1.len() + text2.len());
+    main_internal(dmp, text1, text2, checklines, true, limits, &mut Vec::new())
+}
+
 fn main_internal(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     text1: &str,
     text2: &str,
     checklines: bool,
     allow_words: bool,
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Vec<Diff> {
     // Trivial outcomes build their single `Diff` straight from the `&str`
@@ -199,12 +369,12 @@ fn main_internal(
     if text1.is_empty() && text2.is_empty() {
         return vec![];
     } else if text1.is_empty() {
-        return vec![Diff::new(1, text2.to_string())];
+        return vec![Diff::new(Operation::Insert, text2.to_string())];
     } else if text2.is_empty() {
-        return vec![Diff::new(-1, text1.to_string())];
+        return vec![Diff::new(Operation::Delete, text1.to_string())];
     }
     if text1 == text2 {
-        return vec![Diff::new(0, text1.to_string())];
+        return vec![Diff::new(Operation::Equal, text1.to_string())];
     }
     materialize(diff_str_tokens(
         dmp,
@@ -212,7 +382,7 @@ fn main_internal(
         text2,
         checklines,
         allow_words,
-        deadline,
+        limits,
         scratch,
     ))
 }
@@ -222,24 +392,24 @@ fn main_internal(
 /// caller that stays in token space (line/word mode, rediff) uses this
 /// instead of `main_internal` so no intermediate `String` is built.
 fn diff_str_tokens(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     text1: &str,
     text2: &str,
     checklines: bool,
     allow_words: bool,
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Vec<TDiff> {
     // str-level fast paths: the trivial outcomes skip the char materialization.
     if text1.is_empty() && text2.is_empty() {
         return vec![];
     } else if text1.is_empty() {
-        return vec![TDiff::new(1, text2.chars().collect())];
+        return vec![TDiff::new(Operation::Insert, text2.chars().collect())];
     } else if text2.is_empty() {
-        return vec![TDiff::new(-1, text1.chars().collect())];
+        return vec![TDiff::new(Operation::Delete, text1.chars().collect())];
     }
     if text1 == text2 {
-        return vec![TDiff::new(0, text1.chars().collect())];
+        return vec![TDiff::new(Operation::Equal, text1.chars().collect())];
     }
 
     // ASCII fast path: bytes are a bijection with chars there, so the same
@@ -249,13 +419,14 @@ fn diff_str_tokens(
     // packed placeholder texts (ids happen to be < 128 for small documents)
     // are eligible.
     if text1.is_ascii() && text2.is_ascii() {
+        limits.record_ascii();
         return main_slices(
             dmp,
             text1.as_bytes(),
             text2.as_bytes(),
             checklines,
             allow_words,
-            deadline,
+            limits,
             scratch,
         );
     }
@@ -268,7 +439,7 @@ fn diff_str_tokens(
         &char2,
         checklines,
         allow_words,
-        deadline,
+        limits,
         scratch,
     )
 }
@@ -278,30 +449,31 @@ fn diff_str_tokens(
 /// `diff_str_tokens` — an all-ASCII pair recurses over bytes — and returns
 /// token pieces.
 fn diff_char_tokens(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     old: &[char],
     new: &[char],
     checklines: bool,
     allow_words: bool,
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Vec<TDiff> {
     if old.is_empty() && new.is_empty() {
         return vec![];
     } else if old.is_empty() {
-        return vec![TDiff::new(1, new.to_vec())];
+        return vec![TDiff::new(Operation::Insert, new.to_vec())];
     } else if new.is_empty() {
-        return vec![TDiff::new(-1, old.to_vec())];
+        return vec![TDiff::new(Operation::Delete, old.to_vec())];
     }
     if old == new {
-        return vec![TDiff::new(0, old.to_vec())];
+        return vec![TDiff::new(Operation::Equal, old.to_vec())];
     }
     if old.iter().all(char::is_ascii) && new.iter().all(char::is_ascii) {
+        limits.record_ascii();
         let b1: Vec<u8> = old.iter().map(|&c| c as u8).collect();
         let b2: Vec<u8> = new.iter().map(|&c| c as u8).collect();
-        return main_slices(dmp, &b1, &b2, checklines, allow_words, deadline, scratch);
+        return main_slices(dmp, &b1, &b2, checklines, allow_words, limits, scratch);
     }
-    main_slices(dmp, old, new, checklines, allow_words, deadline, scratch)
+    main_slices(dmp, old, new, checklines, allow_words, limits, scratch)
 }
 
 /// `diff_main` over char slices, for crate internals that already hold char
@@ -309,7 +481,7 @@ fn diff_char_tokens(
 /// grapheme mode round-trips through `diff_main` to keep cluster packing —
 /// but the char-mode path skips the String materialization entirely.
 pub(crate) fn diff_main_chars(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     old: &[char],
     new: &[char],
     checklines: bool,
@@ -322,14 +494,14 @@ pub(crate) fn diff_main_chars(
             return dmp.diff_main(&t1, &t2, checklines);
         }
     }
-    let deadline = dmp.deadline_from_now();
+    let limits = Limits::from_dmp(dmp);
     materialize(diff_char_tokens(
         dmp,
         old,
         new,
         checklines,
         true,
-        deadline,
+        &limits,
         &mut Vec::new(),
     ))
 }
@@ -344,26 +516,29 @@ pub(crate) fn diff_main_chars(
 /// never be re-tokenized (some ids alias whitespace chars) and because a
 /// whitespace-free block would recurse onto itself.
 fn main_slices<T: DiffToken>(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     old: &[T],
     new: &[T],
     checklines: bool,
     allow_words: bool,
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Vec<TDiff> {
     // check for empty text
     if old.is_empty() && new.is_empty() {
         return vec![];
     } else if old.is_empty() {
-        return vec![TDiff::new(1, T::to_tokens(new))];
+        limits.settle(&[], new);
+        return vec![TDiff::new(Operation::Insert, T::to_tokens(new))];
     } else if new.is_empty() {
-        return vec![TDiff::new(-1, T::to_tokens(old))];
+        limits.settle(old, &[]);
+        return vec![TDiff::new(Operation::Delete, T::to_tokens(old))];
     }
 
     // check for equality
     if old == new {
-        return vec![TDiff::new(0, T::to_tokens(old))];
+        limits.settle(old, new);
+        return vec![TDiff::new(Operation::Equal, T::to_tokens(old))];
     }
 
     // Trim off common prefix and suffix (speedup).
@@ -375,7 +550,8 @@ fn main_slices<T: DiffToken>(
     let mut diffs: Vec<TDiff> = Vec::new();
     // Restore the prefix, compute the diff on the middle block, restore the suffix.
     if prefix > 0 {
-        diffs.push(TDiff::new(0, T::to_tokens(&old[..prefix])));
+        limits.settle(&old[..prefix], &new[..prefix]);
+        diffs.push(TDiff::new(Operation::Equal, T::to_tokens(&old[..prefix])));
     }
     diffs.extend(compute(
         dmp,
@@ -383,11 +559,15 @@ fn main_slices<T: DiffToken>(
         mid2,
         checklines,
         allow_words,
-        deadline,
+        limits,
         scratch,
     ));
     if suffix > 0 {
-        diffs.push(TDiff::new(0, T::to_tokens(&old[old.len() - suffix..])));
+        limits.settle(&old[old.len() - suffix..], &new[new.len() - suffix..]);
+        diffs.push(TDiff::new(
+            Operation::Equal,
+            T::to_tokens(&old[old.len() - suffix..]),
+        ));
     }
     dmp.diff_cleanup_merge_impl(&mut diffs);
     diffs
@@ -396,21 +576,23 @@ fn main_slices<T: DiffToken>(
 /// Find the differences between two token slices that share no common prefix
 /// or suffix and are not both empty.
 fn compute<T: DiffToken>(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     old: &[T],
     new: &[T],
     checklines: bool,
     allow_words: bool,
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Vec<TDiff> {
     if old.is_empty() {
         // Just add some text (speedup).
-        return vec![TDiff::new(1, T::to_tokens(new))];
+        limits.settle(&[], new);
+        return vec![TDiff::new(Operation::Insert, T::to_tokens(new))];
     }
     if new.is_empty() {
         // Just delete some text (speedup).
-        return vec![TDiff::new(-1, T::to_tokens(old))];
+        limits.settle(old, &[]);
+        return vec![TDiff::new(Operation::Delete, T::to_tokens(old))];
     }
     {
         let (long, short) = if old.len() >= new.len() {
@@ -420,30 +602,42 @@ fn compute<T: DiffToken>(
         };
         if let Some(i) = engine::contains(long, short) {
             // Shorter text is inside the longer text (speedup).
-            let op = if old.len() > new.len() { -1 } else { 1 };
+            let op = if old.len() > new.len() {
+                Operation::Delete
+            } else {
+                Operation::Insert
+            };
             let mut diffs: Vec<TDiff> = Vec::new();
             if i != 0 {
                 diffs.push(TDiff::new(op, T::to_tokens(&long[..i])));
             }
-            diffs.push(TDiff::new(0, T::to_tokens(short)));
+            diffs.push(TDiff::new(Operation::Equal, T::to_tokens(short)));
             if i + short.len() != long.len() {
                 diffs.push(TDiff::new(op, T::to_tokens(&long[i + short.len()..])));
             }
+            limits.settle(old, new);
             return diffs;
         }
         if short.len() == 1 {
             // Single character string.
             // After the previous speedup, the character can't be an equality.
+            limits.settle(old, new);
             return vec![
-                TDiff::new(-1, T::to_tokens(old)),
-                TDiff::new(1, T::to_tokens(new)),
+                TDiff::new(Operation::Delete, T::to_tokens(old)),
+                TDiff::new(Operation::Insert, T::to_tokens(new)),
             ];
         }
     }
 
+    // Patience and histogram anchor on whole lines, so they go first: a
+    // half-match could otherwise split the texts mid-line.
+    if checklines && dmp.diff_algorithm != DiffAlgorithm::Myers {
+        return line_mode(dmp, old, new, limits, scratch);
+    }
+
     // Check to see if the problem can be split in two (only when a deadline is
     // set: half-match trades optimality for speed).
-    if deadline.is_some() {
+    if limits.is_bounded() {
         if let Some(hm) = engine::half_match(old, new) {
             // A half-match was found, send both pairs off for separate processing.
             let mid_common: Vec<char> = T::to_tokens(&old[hm.old_a..hm.old_a + hm.common]);
@@ -453,17 +647,21 @@ fn compute<T: DiffToken>(
                 &new[..hm.new_a],
                 checklines,
                 allow_words,
-                deadline,
+                limits,
                 scratch,
             );
-            diffs.push(TDiff::new(0, mid_common));
+            limits.settle(
+                &old[hm.old_a..hm.old_a + hm.common],
+                &new[hm.new_a..hm.new_a + hm.common],
+            );
+            diffs.push(TDiff::new(Operation::Equal, mid_common));
             diffs.extend(main_slices(
                 dmp,
                 &old[hm.old_a + hm.common..],
                 &new[hm.new_a + hm.common..],
                 checklines,
                 allow_words,
-                deadline,
+                limits,
                 scratch,
             ));
             return diffs;
@@ -471,25 +669,26 @@ fn compute<T: DiffToken>(
     }
 
     if checklines && old.len() > 100 && new.len() > 100 {
-        return line_mode(dmp, old, new, deadline, scratch);
+        return line_mode(dmp, old, new, limits, scratch);
     }
     if allow_words && dmp.word_mode && old.len() > 100 && new.len() > 100 {
-        return word_mode(dmp, old, new, deadline, scratch);
+        return word_mode(dmp, old, new, limits, scratch);
     }
-    bisect_diff(dmp, old, new, allow_words, deadline, scratch)
+    bisect_diff(dmp, old, new, allow_words, limits, scratch)
 }
 
 /// Split on the Myers middle snake and recurse, or emit delete+insert when
 /// there is no overlap (or the deadline expired).
 fn bisect_diff<T: DiffToken>(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     old: &[T],
     new: &[T],
     allow_words: bool,
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Vec<TDiff> {
-    match engine::bisect(old, new, deadline, scratch) {
+    limits.record(|s| s.bisects += 1);
+    match engine::bisect(old, new, limits, scratch) {
         Some((x, y)) => {
             // Compute both diffs serially on the split halves.
             let mut diffs = main_slices(
@@ -498,7 +697,7 @@ fn bisect_diff<T: DiffToken>(
                 &new[..y],
                 false,
                 allow_words,
-                deadline,
+                limits,
                 scratch,
             );
             diffs.extend(main_slices(
@@ -507,16 +706,18 @@ fn bisect_diff<T: DiffToken>(
                 &new[y..],
                 false,
                 allow_words,
-                deadline,
+                limits,
                 scratch,
             ));
             diffs
         }
         None => {
-            // Number of diffs equals number of characters, no commonality at all.
+            // Number of diffs equals number of characters, no commonality at
+            // all — or the bisect was stopped.
+            limits.settle(old, new);
             vec![
-                TDiff::new(-1, T::to_tokens(old)),
-                TDiff::new(1, T::to_tokens(new)),
+                TDiff::new(Operation::Delete, T::to_tokens(old)),
+                TDiff::new(Operation::Insert, T::to_tokens(new)),
             ]
         }
     }
@@ -525,12 +726,13 @@ fn bisect_diff<T: DiffToken>(
 /// Line-mode speedup: diff on packed line ids first, then rediff the
 /// replacement blocks character by character.
 fn line_mode<T: DiffToken>(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     old: &[T],
     new: &[T],
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Vec<TDiff> {
+    limits.record(|s| s.line_mode_passes += 1);
     let mut diffs: Vec<TDiff> =
         if crate::tokenize::packs_to_one_line(old) && crate::tokenize::packs_to_one_line(new) {
             // Single-line inputs pack to one fresh token each (the texts differ,
@@ -538,8 +740,8 @@ fn line_mode<T: DiffToken>(
             // chars is deterministically delete+insert. Start from the
             // rehydrated form directly and skip the packing.
             vec![
-                TDiff::new(-1, T::to_tokens(old)),
-                TDiff::new(1, T::to_tokens(new)),
+                TDiff::new(Operation::Delete, T::to_tokens(old)),
+                TDiff::new(Operation::Insert, T::to_tokens(new)),
             ]
         } else {
             // Scan the text on a line-by-line basis first.
@@ -547,19 +749,231 @@ fn line_mode<T: DiffToken>(
 
             // Packed placeholder chars must never be re-tokenized: no line mode
             // (checklines = false) and no word mode (allow_words = false).
+            // Progress is settled on the rehydrated pieces, in rediff_blocks.
             let mut diffs: Vec<TDiff> =
-                diff_str_tokens(dmp, &text3, &text4, false, false, deadline, scratch);
+                limits.muted(|| diff_line_tokens(dmp, &text3, &text4, limits, scratch));
 
             // Convert the diff back to original text.
             crate::tokenize::chars_tolines_arena(&mut diffs, &store);
             diffs
         };
-    // Eliminate freak matches (e.g. blank lines)
-    dmp.diff_cleanup_semantic_impl(&mut diffs);
+    // Eliminate freak matches (e.g. blank lines). Patience and histogram
+    // anchors are rare lines, matched on purpose: keep them.
+    if dmp.diff_algorithm == DiffAlgorithm::Myers {
+        dmp.diff_cleanup_semantic_impl(&mut diffs);
+    }
 
     // Rediff any replacement blocks, this time character-by-character —
     // where the opt-in word mode may engage on large blocks.
-    rediff_blocks(dmp, diffs, true, deadline, scratch)
+    rediff_blocks(dmp, diffs, true, limits, scratch)
+}
+
+/// The diff core over raw bytes (`diff_bytes`): the byte recursion of the
+/// ASCII fast path, line mode included, on any bytes. Pieces carry each byte
+/// as the char of the same value.
+pub(crate) fn diff_byte_tokens(dmp: &Dmp, old: &[u8], new: &[u8]) -> Vec<TDiff> {
+    let limits = Limits::from_dmp(dmp);
+    main_slices(dmp, old, new, true, true, &limits, &mut Vec::new())
+}
+
+/// Token-atomic word diff behind `diff_words`: pack each word of the chosen
+/// vocabulary into one token, diff in token space, and rehydrate. Replacement
+/// blocks are not rediffed, so changed words stay whole.
+fn diff_word_tokens(dmp: &Dmp, old: &str, new: &str, limits: &Limits) -> Vec<TDiff> {
+    let (text1, text2, store) = match dmp.word_vocabulary {
+        crate::types::WordVocabulary::Whitespace if old.is_ascii() && new.is_ascii() => {
+            crate::tokenize::words_tochars_arena(old.as_bytes(), new.as_bytes())
+        }
+        crate::types::WordVocabulary::Whitespace => {
+            let char1: Vec<char> = old.chars().collect();
+            let char2: Vec<char> = new.chars().collect();
+            crate::tokenize::words_tochars_arena(&char1, &char2)
+        }
+        #[cfg(feature = "grapheme")]
+        crate::types::WordVocabulary::Unicode => {
+            crate::tokenize::tokens_tochars_arena(old, new, &crate::UnicodeWordTokenizer)
+        }
+    };
+    limits.begin_packed(old.len() + new.len(), || store.widths());
+    let mut diffs = diff_str_tokens(dmp, &text1, &text2, false, false, limits, &mut Vec::new());
+    crate::tokenize::chars_tolines_arena(&mut diffs, &store);
+    diffs
+}
+
+/// The words of each `op` diff, split one diff at a time.
+fn edit_tokens<'a>(dmp: &Dmp, diffs: &'a [Diff], op: Operation) -> Vec<&'a str> {
+    let edits = diffs.iter().filter(|d| d.operation == op);
+    match dmp.word_vocabulary {
+        crate::types::WordVocabulary::Whitespace => {
+            edits.flat_map(|d| d.text.split_whitespace()).collect()
+        }
+        #[cfg(feature = "grapheme")]
+        crate::types::WordVocabulary::Unicode => {
+            use unicode_segmentation::UnicodeSegmentation;
+            edits
+                .flat_map(|d| d.text.split_word_bounds())
+                .filter(|t| !t.trim().is_empty())
+                .collect()
+        }
+    }
+}
+
+/// `diff_words` under caller-built limits; the progress total is the byte
+/// length of both inputs.
+pub(crate) fn diff_words_limited(dmp: &Dmp, old: &str, new: &str, limits: &Limits) -> Vec<Diff> {
+    materialize(diff_word_tokens(dmp, old, new, limits))
+}
+
+/// Word-granular diff of two short texts (the intra-line highlights of
+/// `diff_lines`): the `diff_words` pieces, semantically cleaned for display.
+pub(crate) fn diff_word_pieces(dmp: &Dmp, old: &str, new: &str) -> Vec<Diff> {
+    let limits = Limits::from_dmp(dmp);
+    let mut diffs = diff_word_tokens(dmp, old, new, &limits);
+    dmp.diff_cleanup_semantic_impl(&mut diffs);
+    materialize(diffs)
+}
+
+/// Line-granular diff for the line-oriented output formats: each piece's
+/// data holds packed line ids (one char per line) to resolve through the
+/// returned arena. Unlike line mode there is no cleanup and no char-level
+/// rediff, so every changed line shows up whole.
+pub(crate) fn diff_line_ids(
+    dmp: &Dmp,
+    old: &str,
+    new: &str,
+) -> (Vec<TDiff>, crate::tokenize::LineArena) {
+    let limits = Limits::from_dmp(dmp);
+    let (text1, text2, store) = if old.is_ascii() && new.is_ascii() {
+        crate::tokenize::lines_tochars_arena(old.as_bytes(), new.as_bytes())
+    } else {
+        let char1: Vec<char> = old.chars().collect();
+        let char2: Vec<char> = new.chars().collect();
+        crate::tokenize::lines_tochars_arena(&char1, &char2)
+    };
+    let diffs = diff_line_tokens(dmp, &text1, &text2, &limits, &mut Vec::new());
+    (diffs, store)
+}
+
+/// Diff two texts of packed line ids with `dmp.diff_algorithm`.
+fn diff_line_tokens(
+    dmp: &Dmp,
+    text1: &str,
+    text2: &str,
+    limits: &Limits,
+    scratch: &mut Vec<i32>,
+) -> Vec<TDiff> {
+    if dmp.diff_algorithm == DiffAlgorithm::Myers {
+        return diff_str_tokens(dmp, text1, text2, false, false, limits, scratch);
+    }
+    let ids1: Vec<char> = text1.chars().collect();
+    let ids2: Vec<char> = text2.chars().collect();
+    let mut diffs = if dmp.diff_algorithm == DiffAlgorithm::Histogram {
+        histogram(dmp, &ids1, &ids2, limits, scratch)
+    } else {
+        patience(dmp, &ids1, &ids2, limits, scratch)
+    };
+    dmp.diff_cleanup_merge_impl(&mut diffs);
+    diffs
+}
+
+/// Histogram diff over packed line ids: trim the common ends, match the run
+/// of common lines whose rarest line is least frequent, and recurse on both
+/// sides of it; a range with no such run goes to the Myers recursion. Each
+/// search is charged its range length against the budget, and a stopped
+/// diff leaves the range as a coarse delete+insert.
+fn histogram(
+    dmp: &Dmp,
+    old: &[char],
+    new: &[char],
+    limits: &Limits,
+    scratch: &mut Vec<i32>,
+) -> Vec<TDiff> {
+    let prefix = engine::common_prefix(old, new);
+    let suffix = engine::common_suffix(&old[prefix..], &new[prefix..]);
+    let mid1 = &old[prefix..old.len() - suffix];
+    let mid2 = &new[prefix..new.len() - suffix];
+
+    let mut diffs: Vec<TDiff> = Vec::new();
+    if prefix > 0 {
+        diffs.push(TDiff::new(Operation::Equal, old[..prefix].to_vec()));
+    }
+    let cost = (mid1.len() + mid2.len()) as u64;
+    if !mid1.is_empty() && !mid2.is_empty() && limits.step_should_stop(cost) {
+        diffs.push(TDiff::new(Operation::Delete, mid1.to_vec()));
+        diffs.push(TDiff::new(Operation::Insert, mid2.to_vec()));
+    } else if let Some((a, b, len)) = engine::histogram_lcs(mid1, mid2) {
+        diffs.extend(histogram(dmp, &mid1[..a], &mid2[..b], limits, scratch));
+        diffs.push(TDiff::new(Operation::Equal, mid1[a..a + len].to_vec()));
+        diffs.extend(histogram(
+            dmp,
+            &mid1[a + len..],
+            &mid2[b + len..],
+            limits,
+            scratch,
+        ));
+    } else {
+        diffs.extend(diff_char_tokens(
+            dmp, mid1, mid2, false, false, limits, scratch,
+        ));
+    }
+    if suffix > 0 {
+        diffs.push(TDiff::new(
+            Operation::Equal,
+            old[old.len() - suffix..].to_vec(),
+        ));
+    }
+    diffs
+}
+
+/// Patience diff over packed line ids: trim the common ends, match the lines
+/// unique to both sides as anchors, and recurse into the gaps between them;
+/// a gap with no unique lines left goes to the Myers recursion. As in
+/// `histogram`, each anchor search is charged its range length and a stopped
+/// diff leaves the range as a coarse delete+insert.
+fn patience(
+    dmp: &Dmp,
+    old: &[char],
+    new: &[char],
+    limits: &Limits,
+    scratch: &mut Vec<i32>,
+) -> Vec<TDiff> {
+    let prefix = engine::common_prefix(old, new);
+    let suffix = engine::common_suffix(&old[prefix..], &new[prefix..]);
+    let mid1 = &old[prefix..old.len() - suffix];
+    let mid2 = &new[prefix..new.len() - suffix];
+
+    let mut diffs: Vec<TDiff> = Vec::new();
+    if prefix > 0 {
+        diffs.push(TDiff::new(Operation::Equal, old[..prefix].to_vec()));
+    }
+    let cost = (mid1.len() + mid2.len()) as u64;
+    if !mid1.is_empty() && !mid2.is_empty() && limits.step_should_stop(cost) {
+        diffs.push(TDiff::new(Operation::Delete, mid1.to_vec()));
+        diffs.push(TDiff::new(Operation::Insert, mid2.to_vec()));
+    } else {
+        let anchors = engine::unique_anchors(mid1, mid2);
+        if anchors.is_empty() {
+            diffs.extend(diff_char_tokens(
+                dmp, mid1, mid2, false, false, limits, scratch,
+            ));
+        } else {
+            let (mut i, mut j) = (0, 0);
+            for (a, b) in anchors {
+                diffs.extend(patience(dmp, &mid1[i..a], &mid2[j..b], limits, scratch));
+                diffs.push(TDiff::new(Operation::Equal, vec![mid1[a]]));
+                i = a + 1;
+                j = b + 1;
+            }
+            diffs.extend(patience(dmp, &mid1[i..], &mid2[j..], limits, scratch));
+        }
+    }
+    if suffix > 0 {
+        diffs.push(TDiff::new(
+            Operation::Equal,
+            old[old.len() - suffix..].to_vec(),
+        ));
+    }
+    diffs
 }
 
 /// Word-mode speedup (opt-in via `Dmp::word_mode`): the word-level analog of
@@ -569,16 +983,17 @@ fn line_mode<T: DiffToken>(
 /// word boundaries first, so it is not byte-identical to the reference
 /// implementation's char-level diff.
 fn word_mode<T: DiffToken>(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     old: &[T],
     new: &[T],
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Vec<TDiff> {
+    limits.record(|s| s.word_mode_passes += 1);
     let (text3, text4, store) = crate::tokenize::words_tochars_arena(old, new);
 
     let mut diffs: Vec<TDiff> =
-        diff_str_tokens(dmp, &text3, &text4, false, false, deadline, scratch);
+        limits.muted(|| diff_str_tokens(dmp, &text3, &text4, false, false, limits, scratch));
 
     crate::tokenize::chars_tolines_arena(&mut diffs, &store);
     // Unlike line mode, NO semantic cleanup before the rediff: word-level
@@ -589,20 +1004,20 @@ fn word_mode<T: DiffToken>(
 
     // Word-level rediffs must not re-enter word mode: a whitespace-free
     // block packs into a single token and would recurse onto itself.
-    rediff_blocks(dmp, diffs, false, deadline, scratch)
+    rediff_blocks(dmp, diffs, false, limits, scratch)
 }
 
 /// Shared tail of the token-mode speedups: rediff every replacement block of
 /// `diffs` at the next-finer granularity.
 fn rediff_blocks(
-    dmp: &mut Dmp,
+    dmp: &Dmp,
     mut diffs: Vec<TDiff>,
     allow_words: bool,
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Vec<TDiff> {
     // Add a dummy entry at the end.
-    diffs.push(TDiff::new(0, vec![]));
+    diffs.push(TDiff::new(Operation::Equal, vec![]));
     let mut count_delete = 0;
     let mut count_insert = 0;
     let mut text_delete: Vec<char> = vec![];
@@ -610,15 +1025,17 @@ fn rediff_blocks(
     let mut pointer = 0;
     let mut temp: Vec<TDiff> = vec![];
     while pointer < diffs.len() {
-        if diffs[pointer].operation == 1 {
+        if diffs[pointer].operation == Operation::Insert {
             count_insert += 1;
             text_insert.extend_from_slice(&diffs[pointer].data);
-        } else if diffs[pointer].operation == -1 {
+        } else if diffs[pointer].operation == Operation::Delete {
             count_delete += 1;
             text_delete.extend_from_slice(&diffs[pointer].data);
         } else {
-            // Upon reaching an equality, check for prior redundancies.
-            if count_delete >= 1 && count_insert >= 1 {
+            // Upon reaching an equality, check for prior redundancies. A
+            // cancelled diff keeps the remaining blocks coarse.
+            limits.settle(&diffs[pointer].data, &diffs[pointer].data);
+            if count_delete >= 1 && count_insert >= 1 && !limits.rediff_should_stop() {
                 // Delete the offending records and add the merged ones.
                 let sub_diff = diff_char_tokens(
                     dmp,
@@ -626,7 +1043,7 @@ fn rediff_blocks(
                     &text_insert,
                     false,
                     allow_words,
-                    deadline,
+                    limits,
                     scratch,
                 );
                 for z in sub_diff {
@@ -637,11 +1054,18 @@ fn rediff_blocks(
                     diffs[pointer].data.clone(),
                 ));
             } else {
+                limits.settle(&text_delete, &text_insert);
                 if !text_delete.is_empty() {
-                    temp.push(TDiff::new(-1, std::mem::take(&mut text_delete)));
+                    temp.push(TDiff::new(
+                        Operation::Delete,
+                        std::mem::take(&mut text_delete),
+                    ));
                 }
                 if !text_insert.is_empty() {
-                    temp.push(TDiff::new(1, std::mem::take(&mut text_insert)));
+                    temp.push(TDiff::new(
+                        Operation::Insert,
+                        std::mem::take(&mut text_insert),
+                    ));
                 }
                 temp.push(TDiff::new(
                     diffs[pointer].operation,
@@ -687,17 +1111,25 @@ mod tests {
                 let mut dmp = Dmp::new();
                 dmp.word_mode = word_mode;
                 let byte_diff = main_slices(
-                    &mut dmp,
+                    &dmp,
                     t1.as_bytes(),
                     t2.as_bytes(),
                     true,
                     true,
-                    None,
+                    &Limits::new(None),
                     &mut Vec::new(),
                 );
                 let c1: Vec<char> = t1.chars().collect();
                 let c2: Vec<char> = t2.chars().collect();
-                let char_diff = main_slices(&mut dmp, &c1, &c2, true, true, None, &mut Vec::new());
+                let char_diff = main_slices(
+                    &dmp,
+                    &c1,
+                    &c2,
+                    true,
+                    true,
+                    &Limits::new(None),
+                    &mut Vec::new(),
+                );
                 assert_eq!(byte_diff.len(), char_diff.len(), "{t1:?} vs {t2:?}");
                 for (b, c) in byte_diff.iter().zip(char_diff.iter()) {
                     assert_eq!(b.operation, c.operation, "{t1:?} vs {t2:?}");
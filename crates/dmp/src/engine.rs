@@ -1,9 +1,11 @@
 // Generic DMP diff primitives over token slices: common prefix/suffix, KMP
-// substring search, half-match, and the Myers middle-snake bisect. Pure
-// functions over `&[T: Eq]` — no Dmp state, no text; orchestration and
-// text materialization live in diff.rs.
+// substring search, half-match, the Myers middle-snake bisect, the histogram
+// run search and the patience anchors. Pure functions over `&[T: Eq]` — no
+// Dmp state, no text; orchestration and text materialization live in
+// diff.rs.
 
-use std::time::Instant;
+use crate::outcome::Limits;
+use std::hash::Hash;
 
 // Chunk width for the common-run scans. Runs are scanned token by token
 // first — most probes (e.g. the bisect snake walk) mismatch within a couple
@@ -477,7 +479,8 @@ fn snake_rev<T: Eq>(old: &[T], new: &[T], mut x: usize, mut y: usize) -> (usize,
 
 /// DMP diff_bisect middle-snake search. Returns the split point (x, y) where
 /// the forward and reverse d-paths overlap, or None when the inputs share no
-/// overlap (or the deadline expired) and the caller should emit delete+insert.
+/// overlap (or the deadline expired or the diff was cancelled, as recorded
+/// in `limits`) and the caller should emit delete+insert.
 ///
 /// The classic ports guard the snake walks against negative x/y (Python's
 /// arr[-1] wraps from the end), but the Myers invariants make that
@@ -489,7 +492,7 @@ fn snake_rev<T: Eq>(old: &[T], new: &[T], mut x: usize, mut y: usize) -> (usize,
 pub(crate) fn bisect<T: Eq>(
     old: &[T],
     new: &[T],
-    deadline: Option<Instant>,
+    limits: &Limits,
     scratch: &mut Vec<i32>,
 ) -> Option<(usize, usize)> {
     let text1_length = old.len() as i32;
@@ -516,10 +519,10 @@ pub(crate) fn bisect<T: Eq>(
     let mut k2start: i32 = 0;
     let mut k2end: i32 = 0;
     for d in 0..max_d {
-        if let Some(deadline) = deadline {
-            if Instant::now() >= deadline {
-                break;
-            }
+        // Budget cost of the step: the diagonals both walks will extend.
+        let cost = (2 * d - k1start - k1end) / 2 + 1 + (2 * d - k2start - k2end) / 2 + 1;
+        if limits.step_should_stop(cost.max(0) as u64) {
+            break;
         }
 
         // Walk the front path one step.
@@ -621,10 +624,113 @@ pub(crate) fn bisect<T: Eq>(
         }
     }
     // Number of diffs equals number of tokens, no commonality at all — or the
-    // deadline expired.
+    // search was stopped.
     None
 }
 
+// Tokens occurring more often than this in the old text never seed a
+// histogram run (git's default).
+const HISTOGRAM_MAX_CHAIN: usize = 64;
+
+/// Histogram diff split (git's xhistogram): the common run of `old` and
+/// `new` whose rarest token occurs least often in `old`, the longest such
+/// run on ties. Tokens occurring more than `HISTOGRAM_MAX_CHAIN` times in
+/// `old` never seed a run. Returns `(old start, new start, length)`, or None
+/// when nothing can seed one.
+pub(crate) fn histogram_lcs<T: Eq + Hash>(old: &[T], new: &[T]) -> Option<(usize, usize, usize)> {
+    let mut occurrences: crate::tokenize::FxMap<&T, Vec<usize>> = crate::tokenize::FxMap::default();
+    for (i, token) in old.iter().enumerate() {
+        occurrences.entry(token).or_default().push(i);
+    }
+    let count = |token: &T| occurrences[token].len();
+    let mut best: Option<(usize, usize, usize)> = None;
+    let mut best_count = HISTOGRAM_MAX_CHAIN + 1;
+    let mut j = 0;
+    while j < new.len() {
+        let mut next_j = j + 1;
+        match occurrences.get(&new[j]) {
+            Some(seeds) if seeds.len() <= best_count => {
+                for &i in seeds {
+                    // Grow the run both ways, tracking its rarest token.
+                    let mut rarest = seeds.len();
+                    let (mut s1, mut s2) = (i, j);
+                    while s1 > 0 && s2 > 0 && old[s1 - 1] == new[s2 - 1] {
+                        s1 -= 1;
+                        s2 -= 1;
+                        rarest = rarest.min(count(&old[s1]));
+                    }
+                    let (mut e1, mut e2) = (i + 1, j + 1);
+                    while e1 < old.len() && e2 < new.len() && old[e1] == new[e2] {
+                        rarest = rarest.min(count(&old[e1]));
+                        e1 += 1;
+                        e2 += 1;
+                    }
+                    // Seeds inside this run would only find it again.
+                    next_j = next_j.max(e2);
+                    if rarest < best_count || best.map_or(true, |(_, _, len)| e1 - s1 > len) {
+                        best = Some((s1, s2, e1 - s1));
+                        best_count = rarest;
+                    }
+                }
+            }
+            _ => {}
+        }
+        j = next_j;
+    }
+    best
+}
+
+/// Patience diff anchors: the tokens occurring exactly once in `old` and
+/// exactly once in `new`, thinned to the longest run whose positions
+/// increase on both sides (found by patience sorting). Returns the
+/// `(old index, new index)` pairs in order; empty when no token is unique.
+pub(crate) fn unique_anchors<T: Eq + Hash>(old: &[T], new: &[T]) -> Vec<(usize, usize)> {
+    // Per token: (count in old, index in old, count in new, index in new).
+    let mut seen: crate::tokenize::FxMap<&T, (u32, usize, u32, usize)> =
+        crate::tokenize::FxMap::default();
+    for (i, token) in old.iter().enumerate() {
+        let entry = seen.entry(token).or_default();
+        entry.0 += 1;
+        entry.1 = i;
+    }
+    for (j, token) in new.iter().enumerate() {
+        if let Some(entry) = seen.get_mut(token) {
+            entry.2 += 1;
+            entry.3 = j;
+        }
+    }
+    // Unique pairs in `new` order; the anchors are their longest subsequence
+    // increasing in `old` order.
+    let pairs: Vec<(usize, usize)> = new
+        .iter()
+        .filter_map(|token| match seen.get(token) {
+            Some(&(1, i, 1, j)) => Some((i, j)),
+            _ => None,
+        })
+        .collect();
+    // piles[p] is the pair ending the best run of length p + 1 found so far,
+    // back[k] the pair before pair k in its run.
+    let mut piles: Vec<usize> = vec![];
+    let mut back: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (k, &(i, _)) in pairs.iter().enumerate() {
+        let p = piles.partition_point(|&top| pairs[top].0 < i);
+        back[k] = p.checked_sub(1).map(|q| piles[q]);
+        if p == piles.len() {
+            piles.push(k);
+        } else {
+            piles[p] = k;
+        }
+    }
+    let mut anchors = vec![];
+    let mut k = piles.last().copied();
+    while let Some(at) = k {
+        anchors.push(pairs[at]);
+        k = back[at];
+    }
+    anchors.reverse();
+    anchors
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -700,6 +806,40 @@ mod tests {
         assert!(!ot("ab", ""));
     }
 
+    #[test]
+    fn histogram_lcs_over_bytes() {
+        // b seeds at old 1 and 4; the run from old 1 ("bca") contains the
+        // rare c, so it wins.
+        assert_eq!(
+            histogram_lcs(b"abcab".as_slice(), b"xbcay".as_slice()),
+            Some((1, 1, 3))
+        );
+        // Rarity beats length: the lone R outranks "xyxy", built of tokens
+        // occurring twice.
+        assert_eq!(
+            histogram_lcs(b"xyxyR".as_slice(), b"Rxyxy".as_slice()),
+            Some((4, 0, 1))
+        );
+        // Equally rare: the longer run wins.
+        assert_eq!(
+            histogram_lcs(b"pq_rst".as_slice(), b"rst-pq".as_slice()),
+            Some((3, 0, 3))
+        );
+        assert_eq!(histogram_lcs(b"abc".as_slice(), b"xyz".as_slice()), None);
+    }
+
+    #[test]
+    fn unique_anchors_over_bytes() {
+        // 'a' and 'b' repeat, so only c, d and e can anchor; e is out of
+        // order against c and d, so the longest increasing run drops it.
+        assert_eq!(
+            unique_anchors(b"acadebb".as_slice(), b"ecadbab".as_slice()),
+            vec![(1, 1), (3, 3)]
+        );
+        assert!(unique_anchors(b"aab".as_slice(), b"abb".as_slice()).is_empty());
+        assert!(unique_anchors(b"".as_slice(), b"abc".as_slice()).is_empty());
+    }
+
     #[test]
     fn half_match_over_bytes() {
         // No half-match: shared run shorter than half the longer input.
@@ -718,12 +858,29 @@ mod tests {
         // "cat" vs "map": the reverse d=2 walk detects overlap at (2, 2)
         // (hand-traced against the pre-rewrite implementation).
         let mut scratch = Vec::new();
-        assert_eq!(bisect(b"cat", b"map", None, &mut scratch), Some((2, 2)));
+        let none = Limits::new(None);
+        assert_eq!(bisect(b"cat", b"map", &none, &mut scratch), Some((2, 2)));
         // No shared tokens at all: no overlap to split on.
-        assert_eq!(bisect(b"abc", b"xyz", None, &mut scratch), None);
+        assert_eq!(bisect(b"abc", b"xyz", &none, &mut scratch), None);
+        assert_eq!(none.truncated(), None);
         // Expired deadline: give up immediately.
-        let past = Instant::now() - std::time::Duration::from_secs(1);
-        assert_eq!(bisect(b"cat", b"map", Some(past), &mut scratch), None);
+        let past = Limits::new(Some(
+            std::time::Instant::now() - std::time::Duration::from_secs(1),
+        ));
+        assert_eq!(bisect(b"cat", b"map", &past, &mut scratch), None);
+        assert_eq!(past.truncated(), Some(crate::outcome::Truncation::Deadline));
+        // Budget: the three steps cost 2 + 4 + 6 diagonals; a step that does
+        // not fit in what is left is not taken.
+        let mut dmp = crate::Dmp::new();
+        dmp.diff_budget = Some(12);
+        let fits = Limits::from_dmp(&dmp);
+        assert_eq!(bisect(b"cat", b"map", &fits, &mut scratch), Some((2, 2)));
+        assert_eq!(fits.stats().steps, 12);
+        dmp.diff_budget = Some(11);
+        let short = Limits::from_dmp(&dmp);
+        assert_eq!(bisect(b"cat", b"map", &short, &mut scratch), None);
+        assert_eq!(short.truncated(), Some(crate::outcome::Truncation::Budget));
+        assert_eq!(short.stats().steps, 6);
     }
 
     #[test]
@@ -0,0 +1,83 @@
+// Structured parse errors for the wire formats (delta, patch text and unified
+// diffs), returned by the fallible parsers and rendered verbatim by the
+// panicking ones.
+
+use std::fmt;
+
+/// Why a delta, patch text or unified diff failed to parse.
+///
+/// `index` locates the failure: the zero-based line of patch text or unified
+/// diff (split on `\n`), or the zero-based token of a delta (split on `\t`, blank tokens
+/// included). Patch-only variants say "patch" in their message, delta-only
+/// variants say "delta"; only an illegal escape can come from either.
+#[non_exhaustive]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// A patch line where an `@@ -a,b +c,d @@` hunk header was expected, or
+    /// a unified diff hunk before any `---`/`+++` file header.
+    BadHeader { index: usize, text: String },
+    /// A hunk header coordinate that is not a plain digit run within `i32`.
+    BadCoordinates { index: usize, text: String },
+    /// A patch body line whose first char is not one of `+`, `-` or space.
+    InvalidMode { index: usize, text: String },
+    /// A percent escape that does not decode to UTF-8.
+    IllegalEscape { index: usize, text: String },
+    /// A unified diff hunk whose body runs out before, or past, the line
+    /// counts in its header; `index` is the header line.
+    HunkLength { index: usize, text: String },
+    /// A delta token whose first char is not one of `+`, `-` or `=`.
+    InvalidOperation { index: usize, text: String },
+    /// A delta `-`/`=` token whose length is not a number.
+    InvalidLength { index: usize, text: String },
+    /// The delta's `-`/`=` lengths overrun the source text, or stop short of
+    /// it. Lengths count Unicode scalars; `index` is the overrunning token, or
+    /// the token count when the delta ended early.
+    LengthMismatch {
+        index: usize,
+        delta_length: usize,
+        text_length: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::BadHeader { index, text } => {
+                write!(f, "Invalid patch string at line {}: {}", index, text)
+            }
+            ParseError::BadCoordinates { index, text } => {
+                write!(f, "Invalid patch coordinates at line {}: {}", index, text)
+            }
+            ParseError::InvalidMode { index, text } => {
+                write!(f, "Invalid patch mode at line {}: {}", index, text)
+            }
+            ParseError::IllegalEscape { index, text } => {
+                write!(f, "Illegal escape at index {}: {}", index, text)
+            }
+            ParseError::HunkLength { index, text } => {
+                write!(
+                    f,
+                    "Hunk body does not match its line counts at line {}: {}",
+                    index, text
+                )
+            }
+            ParseError::InvalidOperation { index, text } => {
+                write!(f, "Invalid delta operation at token {}: {}", index, text)
+            }
+            ParseError::InvalidLength { index, text } => {
+                write!(f, "Invalid delta length at token {}: {}", index, text)
+            }
+            ParseError::LengthMismatch {
+                index,
+                delta_length,
+                text_length,
+            } => write!(
+                f,
+                "Delta length ({}) does not equal source text length ({}) at token {}",
+                delta_length, text_length, index
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -13,7 +13,7 @@
 //! ```
 //! use diff_match_patch::Dmp;
 //!
-//! let mut dmp = Dmp::new();
+//! let dmp = Dmp::new();
 //! let mut diffs = dmp.diff_main("The quick brown fox.", "The quick red fox.", true);
 //! assert_eq!(dmp.diff_text1(&mut diffs), "The quick brown fox.");
 //! assert_eq!(dmp.diff_text2(&mut diffs), "The quick red fox.");
@@ -24,6 +24,11 @@
 //! assert_eq!(applied, vec![true]);
 //! ```
 //!
+//! Every method takes `&self`, and [`Dmp`], [`Diff`] and [`Patch`] are
+//! `Send + Sync`, so one configured `Dmp` can serve many threads (behind an
+//! `Arc`, say) without a lock. Code written against the older `&mut self`
+//! methods compiles unchanged.
+//!
 //! With the `grapheme` feature enabled, [`Segmentation::Grapheme`] makes
 //! diffs treat extended grapheme clusters (emoji ZWJ sequences, flags,
 //! combining marks) as atomic:
@@ -39,13 +44,37 @@
 //! assert_eq!(diffs.len(), 2);
 //! ```
 
+mod apply;
+mod bytes;
 mod cleanup;
+mod compose;
 mod delta;
 mod diff;
 mod engine;
+mod error;
+mod lines;
 mod match_;
+mod options;
+mod outcome;
 mod patch;
+mod render;
+mod seq;
 mod tokenize;
 mod types;
+mod unified;
 
-pub use types::{Diff, Dmp, Patch, Segmentation};
+pub use apply::{ApplyFailure, ApplyMethod, ApplyPolicy, PatchReport};
+pub use compose::ComposeError;
+pub use error::ParseError;
+pub use lines::{Line, LineHunk, LineOptions, Refine};
+pub use options::DiffOptions;
+pub use outcome::{CancelToken, DiffOutcome, DiffStats, Progress, ProgressHook, Truncation};
+pub use render::{HtmlOptions, InlineOptions, MarkupError};
+pub use seq::SeqDiff;
+#[cfg(feature = "grapheme")]
+pub use tokenize::UnicodeWordTokenizer;
+pub use tokenize::{LineTokenizer, SentenceTokenizer, Tokenizer, WordTokenizer};
+pub use types::{
+    Diff, DiffAlgorithm, DiffChunk, DiffRef, Dmp, Operation, Patch, Segmentation, WordVocabulary,
+};
+pub use unified::{UnifiedFile, UnifiedHunk, UnifiedOptions};
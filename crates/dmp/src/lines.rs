@@ -0,0 +1,210 @@
+// First-class line diff: whole-line ops over the line arena, numbered and
+// grouped into hunks with surrounding context, optionally refined into
+// intra-line word or char highlights for each replaced line pair.
+
+use crate::types::{Diff, Dmp, Operation};
+
+/// How [`Dmp::diff_lines`] highlights the changes inside a replaced line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Refine {
+    /// Whole lines only.
+    #[default]
+    None,
+    /// Words (per `Dmp::word_vocabulary`) are the smallest highlighted unit.
+    Words,
+    /// Char-level highlights, semantically cleaned.
+    Chars,
+}
+
+/// Layout choices for [`Dmp::diff_lines`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineOptions {
+    /// Unchanged lines kept around each change; changes separated by at most
+    /// twice this many share a hunk. `usize::MAX` gives one hunk spanning
+    /// both texts whenever they differ.
+    pub context: usize,
+    pub refine: Refine,
+}
+
+impl Default for LineOptions {
+    fn default() -> Self {
+        LineOptions {
+            context: 3,
+            refine: Refine::None,
+        }
+    }
+}
+
+/// One line of a [`LineHunk`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    pub operation: Operation,
+    /// Zero-based line index in the old text (`None` for insertions).
+    pub old_index: Option<usize>,
+    /// Zero-based line index in the new text (`None` for deletions).
+    pub new_index: Option<usize>,
+    /// The line, with its newline if it has one.
+    pub text: String,
+    /// The refined view of a replaced line: `text` split into equalities and
+    /// chunks of this line's own operation marking what changed. Empty for
+    /// equal lines, unrefined diffs and lines with no counterpart.
+    pub highlights: Vec<Diff>,
+}
+
+/// A run of changed lines with its context. Starts are zero-based counts of
+/// the lines before the hunk, as in [`crate::UnifiedHunk`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineHunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<Line>,
+}
+
+impl Dmp {
+    /// Diff two texts line by line (no char-level rediff, unlike the
+    /// `checklines` speedup of `diff_main`) and group the changes into
+    /// numbered hunks. Identical texts give no hunks.
+    ///
+    /// With `Refine::Words` or `Refine::Chars`, the n-th deleted and n-th
+    /// inserted line of each change block are diffed against each other and
+    /// the result is stored in their `highlights`.
+    ///
+    /// Args:
+    /// text1: Old text.
+    /// text2: New text.
+    /// options: Context size and refinement.
+    ///
+    /// Returns:
+    /// Vector of hunks.
+    pub fn diff_lines(&self, text1: &str, text2: &str, options: &LineOptions) -> Vec<LineHunk> {
+        let (diffs, store) = crate::diff::diff_line_ids(self, text1, text2);
+        let mut lines: Vec<Line> = vec![];
+        let mut old_index = 0;
+        let mut new_index = 0;
+        for diff in &diffs {
+            for &id in &diff.data {
+                let op = diff.operation;
+                lines.push(Line {
+                    operation: op,
+                    old_index: (op != Operation::Insert).then_some(old_index),
+                    new_index: (op != Operation::Delete).then_some(new_index),
+                    text: store.line(id).to_string(),
+                    highlights: vec![],
+                });
+                if op != Operation::Insert {
+                    old_index += 1;
+                }
+                if op != Operation::Delete {
+                    new_index += 1;
+                }
+            }
+        }
+
+        // Each hunk is a range of `lines`; merge while the context windows
+        // of neighbouring changes touch.
+        let mut ranges: Vec<(usize, usize)> = vec![];
+        for (i, line) in lines.iter().enumerate() {
+            if line.operation == Operation::Equal {
+                continue;
+            }
+            let lo = i.saturating_sub(options.context);
+            let hi = usize::min(
+                i.saturating_add(options.context).saturating_add(1),
+                lines.len(),
+            );
+            match ranges.last_mut() {
+                Some(last) if lo <= last.1 => last.1 = hi,
+                _ => ranges.push((lo, hi)),
+            }
+        }
+
+        let mut hunks = vec![];
+        let mut rest = lines.into_iter();
+        let mut taken = 0;
+        let mut old_line = 0;
+        let mut new_line = 0;
+        for (lo, hi) in ranges {
+            for line in rest.by_ref().take(lo - taken) {
+                old_line += (line.operation != Operation::Insert) as usize;
+                new_line += (line.operation != Operation::Delete) as usize;
+            }
+            let mut hunk = LineHunk {
+                old_start: old_line,
+                old_len: 0,
+                new_start: new_line,
+                new_len: 0,
+                lines: rest.by_ref().take(hi - lo).collect(),
+            };
+            taken = hi;
+            hunk.old_len = hunk
+                .lines
+                .iter()
+                .filter(|l| l.operation != Operation::Insert)
+                .count();
+            hunk.new_len = hunk
+                .lines
+                .iter()
+                .filter(|l| l.operation != Operation::Delete)
+                .count();
+            old_line += hunk.old_len;
+            new_line += hunk.new_len;
+            if options.refine != Refine::None {
+                self.refine_hunk(&mut hunk.lines, options.refine);
+            }
+            hunks.push(hunk);
+        }
+        hunks
+    }
+
+    /// Fill in the highlights of each replaced line pair of a hunk.
+    fn refine_hunk(&self, lines: &mut [Line], refine: Refine) {
+        let mut i = 0;
+        while i < lines.len() {
+            if lines[i].operation == Operation::Equal {
+                i += 1;
+                continue;
+            }
+            // A change block: deletions, then insertions.
+            let start = i;
+            while i < lines.len() && lines[i].operation == Operation::Delete {
+                i += 1;
+            }
+            let inserts = i;
+            while i < lines.len() && lines[i].operation == Operation::Insert {
+                i += 1;
+            }
+            let pairs = usize::min(inserts - start, i - inserts);
+            for k in 0..pairs {
+                let (old, new) = (&lines[start + k].text, &lines[inserts + k].text);
+                let diffs = match refine {
+                    Refine::Words => crate::diff::diff_word_pieces(self, old, new),
+                    _ => {
+                        let mut diffs = self.diff_main(old, new, false);
+                        self.diff_cleanup_semantic(&mut diffs);
+                        diffs
+                    }
+                };
+                lines[start + k].highlights = side(&diffs, Operation::Delete);
+                lines[inserts + k].highlights = side(&diffs, Operation::Insert);
+            }
+        }
+    }
+}
+
+/// The chunks of `diffs` one side of the pair sees: equalities and `op`,
+/// with equalities made adjacent by a dropped chunk merged.
+fn side(diffs: &[Diff], op: Operation) -> Vec<Diff> {
+    let mut out: Vec<Diff> = vec![];
+    for diff in diffs {
+        if diff.operation != Operation::Equal && diff.operation != op {
+            continue;
+        }
+        match out.last_mut() {
+            Some(last) if last.operation == diff.operation => last.text.push_str(&diff.text),
+            _ => out.push(diff.clone()),
+        }
+    }
+    out
+}
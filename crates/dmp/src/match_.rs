@@ -3,6 +3,7 @@
 use crate::engine;
 use crate::types::{max, min, min1, Dmp};
 use std::collections::HashMap;
+use std::hash::Hash;
 
 // The historic public API takes &Vec/&mut Vec/&String; frozen by the
 // drop-in compatibility contract.
@@ -17,7 +18,7 @@ impl Dmp {
     ///
     /// Returns:
     /// Best match index or -1.
-    pub fn match_main(&mut self, text1: &str, patern1: &str, mut loc: i32) -> i32 {
+    pub fn match_main(&self, text1: &str, patern1: &str, mut loc: i32) -> i32 {
         // Historical contract: loc clamps on the BYTE length of text1 here
         // even though everything below counts chars; preserved for drop-in
         // compatibility (the internal char-space entry clamps on scalars).
@@ -43,7 +44,7 @@ impl Dmp {
     ///
     /// Returns:
     /// Best match index or -1.
-    pub fn match_bitap(&mut self, text: &Vec<char>, patern: &Vec<char>, loc: i32) -> i32 {
+    pub fn match_bitap(&self, text: &Vec<char>, patern: &Vec<char>, loc: i32) -> i32 {
         bitap(self, text, patern, loc)
     }
 
@@ -56,7 +57,7 @@ impl Dmp {
     ///
     /// Returns:
     /// Overall score for match (0.0 = good, 1.0 = bad).
-    pub fn match_bitap_score(&mut self, e: i32, x: i32, loc: i32, patern: &Vec<char>) -> f32 {
+    pub fn match_bitap_score(&self, e: i32, x: i32, loc: i32, patern: &Vec<char>) -> f32 {
         bitap_score(self, e, x, loc, patern.len())
     }
     /// Initialise the alphabet for the Bitap algorithm.
@@ -68,7 +69,7 @@ impl Dmp {
     /// Hash of character locations. Public i32 view of the internal u64
     /// masks; bit 31 lands in the sign bit exactly as the pre-rewrite
     /// release builds computed it.
-    pub fn match_alphabet(&mut self, patern: &Vec<char>) -> HashMap<char, i32> {
+    pub fn match_alphabet(&self, patern: &Vec<char>) -> HashMap<char, i32> {
         alphabet(patern)
             .into_iter()
             .map(|(ch, mask)| (ch, mask as u32 as i32))
@@ -76,35 +77,75 @@ impl Dmp {
     }
 }
 
-/// `match_main` over char slices with the reference's scalar clamp — the
-/// entry patch_apply uses, so locating a pattern never materializes text.
-pub(crate) fn match_chars(dmp: &mut Dmp, text: &[char], patern: &[char], loc: i32) -> i32 {
+/// `match_main` over token slices (chars, or the bytes of ASCII text) with
+/// the reference's scalar clamp — the entry patch_apply uses, so locating a
+/// pattern never materializes text.
+/// Also returns the match's Bitap score (0.0 = exact at `loc`), meaningless
+/// when the index is -1.
+pub(crate) fn match_chars<T: Copy + Eq + Hash>(
+    dmp: &Dmp,
+    text: &[T],
+    patern: &[T],
+    loc: i32,
+) -> (i32, f32) {
     let loc = max(0, min(loc, text.len() as i32));
     if patern.is_empty() {
-        return loc;
+        return (loc, 0.0);
     }
     if text.is_empty() {
-        return -1;
+        return (-1, 1.0);
     }
-    match_clamped(dmp, text, patern, loc)
+    match_scored(dmp, text, patern, loc)
+}
+
+/// The verbatim occurrence of `patern` nearest `loc`, at most `within` chars
+/// away (the earlier one on a tie), with its Bitap score. -1 if none.
+pub(crate) fn match_exact<T: Eq>(
+    dmp: &Dmp,
+    text: &[T],
+    patern: &[T],
+    loc: i32,
+    within: usize,
+) -> (i32, f32) {
+    let last = text.len() as i64 - patern.len() as i64;
+    let at = |x: i64| (0..=last).contains(&x) && text[x as usize..][..patern.len()] == *patern;
+    let within = usize::min(within, text.len()) as i64;
+    for offset in 0..=within {
+        for x in [loc as i64 - offset, loc as i64 + offset] {
+            if at(x) {
+                return (x as i32, bitap_score(dmp, 0, x as i32, loc, patern.len()));
+            }
+        }
+    }
+    (-1, 1.0)
 }
 
 /// Shared tail of the match entries; `loc` is already clamped by the caller.
-fn match_clamped(dmp: &mut Dmp, text: &[char], patern: &[char], loc: i32) -> i32 {
+fn match_clamped(dmp: &Dmp, text: &[char], patern: &[char], loc: i32) -> i32 {
+    match_scored(dmp, text, patern, loc).0
+}
+
+/// `match_clamped` with the score of the match.
+fn match_scored<T: Copy + Eq + Hash>(dmp: &Dmp, text: &[T], patern: &[T], loc: i32) -> (i32, f32) {
     if text == patern {
         // Shortcut (potentially not guaranteed by the algorithm)
-        return 0;
+        return (0, bitap_score(dmp, 0, 0, loc, patern.len()));
     } else if loc as usize + patern.len() <= text.len()
         && text[(loc as usize)..(loc as usize + patern.len())] == *patern
     {
         // Perfect match at the perfect spot!  (Includes case of null pattern)
-        return loc;
+        return (loc, 0.0);
     }
-    bitap(dmp, text, patern, loc)
+    bitap_scored(dmp, text, patern, loc)
 }
 
 /// DMP match_bitap over token slices.
-fn bitap(dmp: &mut Dmp, text: &[char], patern: &[char], loc: i32) -> i32 {
+fn bitap(dmp: &Dmp, text: &[char], patern: &[char], loc: i32) -> i32 {
+    bitap_scored(dmp, text, patern, loc).0
+}
+
+/// `bitap` with the score of the best match.
+fn bitap_scored<T: Copy + Eq + Hash>(dmp: &Dmp, text: &[T], patern: &[T], loc: i32) -> (i32, f32) {
     // check for maxbits limit.
     if !(dmp.match_maxbits == 0 || patern.len() as i32 <= dmp.match_maxbits) {
         panic!("patern too long for this application");
@@ -115,7 +156,7 @@ fn bitap(dmp: &mut Dmp, text: &[char], patern: &[char], loc: i32) -> i32 {
         panic!("patern too long for this application");
     }
     // Initialise the alphabet.
-    let s: HashMap<char, u64> = alphabet(patern);
+    let s: HashMap<T, u64> = alphabet(patern);
 
     // Highest score beyond which we give up.
     let mut score_threshold: f32 = dmp.match_threshold;
@@ -144,6 +185,7 @@ fn bitap(dmp: &mut Dmp, text: &[char], patern: &[char], loc: i32) -> i32 {
     // Initialise the bit arrays.
     let matchmask: u64 = 1 << (patern.len() - 1);
     best_loc = -1;
+    let mut best_score: f32 = 1.0;
     let mut bin_min: i32;
     let mut bin_mid: i32;
     let mut bin_max: i32 = (patern.len() + text.len()) as i32;
@@ -204,6 +246,7 @@ fn bitap(dmp: &mut Dmp, text: &[char], patern: &[char], loc: i32) -> i32 {
                 if score <= score_threshold {
                     // Told you so.
                     score_threshold = score;
+                    best_score = score;
                     best_loc = j - 1;
                     if best_loc > loc {
                         // When passing loc, don't exceed our current distance from loc.
@@ -222,7 +265,7 @@ fn bitap(dmp: &mut Dmp, text: &[char], patern: &[char], loc: i32) -> i32 {
         }
         last_rd = rd;
     }
-    best_loc
+    (best_loc, best_score)
 }
 
 /// Bitap match quality (0.0 = perfect) from error count and distance; only
@@ -243,8 +286,8 @@ fn bitap_score(dmp: &Dmp, e: i32, x: i32, loc: i32, patern_len: usize) -> f32 {
 
 /// Bitap alphabet over u64 masks (safe for patterns up to match_maxbits = 32,
 /// and beyond up to 64 tokens).
-fn alphabet(patern: &[char]) -> HashMap<char, u64> {
-    let mut s: HashMap<char, u64> = HashMap::new();
+fn alphabet<T: Copy + Eq + Hash>(patern: &[T]) -> HashMap<T, u64> {
+    let mut s: HashMap<T, u64> = HashMap::new();
     for &ch in patern {
         s.insert(ch, 0);
     }
@@ -0,0 +1,307 @@
+// Per-call settings: an immutable override set layered over a `Dmp`'s own
+// fields, so one configured `Dmp` (or one `DiffOptions`) can serve many
+// concurrent callers without anyone mutating shared state.
+
+use crate::apply::ApplyPolicy;
+use crate::outcome::{CancelToken, DiffOutcome, Limits, Progress, ProgressHook};
+use crate::types::{Diff, DiffAlgorithm, Dmp, Patch, Segmentation, WordVocabulary};
+use std::time::Instant;
+
+/// Per-call overrides for the `*_with` entry points. Every setting left
+/// unset falls back to the `Dmp` the call is made on, so
+/// `DiffOptions::new()` reproduces the plain methods exactly.
+///
+/// The type is `Clone + Send + Sync`: build it once and share it, or build
+/// one per request (a tighter timeout for an interactive caller, say).
+///
+/// ```
+/// use diff_match_patch::{DiffOptions, Dmp};
+///
+/// let dmp = Dmp::new();
+/// let fast = DiffOptions::new().diff_timeout(Some(0.05)).checklines(false);
+/// let diffs = dmp.diff_main_with(&fast, "The quick fox.", "The slow fox.");
+/// assert_eq!(diffs[1].text, "quick");
+/// assert_eq!(diffs[2].text, "slow");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiffOptions {
+    diff_timeout: Option<Option<f32>>,
+    diff_budget: Option<Option<u64>>,
+    edit_cost: Option<i32>,
+    checklines: Option<bool>,
+    word_mode: Option<bool>,
+    word_vocabulary: Option<WordVocabulary>,
+    segmentation: Option<Segmentation>,
+    algorithm: Option<DiffAlgorithm>,
+    match_threshold: Option<f32>,
+    match_distance: Option<i32>,
+    match_maxbits: Option<i32>,
+    patch_margin: Option<i32>,
+    patch_delete_threshold: Option<f32>,
+    apply_policy: Option<ApplyPolicy>,
+    cancel: Option<CancelToken>,
+    progress: Option<ProgressHook>,
+}
+
+impl DiffOptions {
+    /// No overrides: every setting comes from the `Dmp`.
+    pub fn new() -> DiffOptions {
+        DiffOptions::default()
+    }
+
+    /// Seconds to map a diff before giving up (`None` for no limit); see
+    /// `Dmp::diff_timeout`.
+    pub fn diff_timeout(mut self, secs: Option<f32>) -> DiffOptions {
+        self.diff_timeout = Some(secs);
+        self
+    }
+
+    /// Bisect work allowed (`None` for no limit); see `Dmp::diff_budget`.
+    pub fn diff_budget(mut self, budget: Option<u64>) -> DiffOptions {
+        self.diff_budget = Some(budget);
+        self
+    }
+
+    /// See `Dmp::edit_cost`.
+    pub fn edit_cost(mut self, cost: i32) -> DiffOptions {
+        self.edit_cost = Some(cost);
+        self
+    }
+
+    /// The `checklines` argument of `diff_main` (line-level first pass).
+    /// Unset means true, as for `patch_make1`.
+    pub fn checklines(mut self, checklines: bool) -> DiffOptions {
+        self.checklines = Some(checklines);
+        self
+    }
+
+    /// See `Dmp::word_mode`.
+    pub fn word_mode(mut self, word_mode: bool) -> DiffOptions {
+        self.word_mode = Some(word_mode);
+        self
+    }
+
+    /// See `Dmp::word_vocabulary`.
+    pub fn word_vocabulary(mut self, vocabulary: WordVocabulary) -> DiffOptions {
+        self.word_vocabulary = Some(vocabulary);
+        self
+    }
+
+    /// See `Dmp::segmentation`.
+    pub fn segmentation(mut self, segmentation: Segmentation) -> DiffOptions {
+        self.segmentation = Some(segmentation);
+        self
+    }
+
+    /// See `Dmp::diff_algorithm`.
+    pub fn algorithm(mut self, algorithm: DiffAlgorithm) -> DiffOptions {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// See `Dmp::match_threshold`.
+    pub fn match_threshold(mut self, threshold: f32) -> DiffOptions {
+        self.match_threshold = Some(threshold);
+        self
+    }
+
+    /// See `Dmp::match_distance`.
+    pub fn match_distance(mut self, distance: i32) -> DiffOptions {
+        self.match_distance = Some(distance);
+        self
+    }
+
+    /// See `Dmp::match_maxbits`.
+    pub fn match_maxbits(mut self, maxbits: i32) -> DiffOptions {
+        self.match_maxbits = Some(maxbits);
+        self
+    }
+
+    /// See `Dmp::patch_margin`.
+    pub fn patch_margin(mut self, margin: i32) -> DiffOptions {
+        self.patch_margin = Some(margin);
+        self
+    }
+
+    /// See `Dmp::patch_delete_threshold`.
+    pub fn patch_delete_threshold(mut self, threshold: f32) -> DiffOptions {
+        self.patch_delete_threshold = Some(threshold);
+        self
+    }
+
+    /// See `Dmp::patch_apply_policy`.
+    pub fn apply_policy(mut self, policy: ApplyPolicy) -> DiffOptions {
+        self.apply_policy = Some(policy);
+        self
+    }
+
+    /// Stop the diffing calls (`diff_main_with`, `diff_main_outcome`,
+    /// `diff_words_with`, `patch_make_with`) early once `token` is
+    /// cancelled. Matching and applying patches ignore it.
+    pub fn cancel(mut self, token: CancelToken) -> DiffOptions {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Call `hook` as a diffing call progresses (the same calls `cancel`
+    /// stops); see [`Progress`] for the unit.
+    pub fn progress(mut self, hook: impl Fn(Progress) + Send + Sync + 'static) -> DiffOptions {
+        self.progress = Some(ProgressHook::new(hook));
+        self
+    }
+}
+
+impl Dmp {
+    /// A private working copy of `self`'s settings with `opts` applied.
+    /// Every setting is plain data, so this is a cheap field copy.
+    fn with_overrides(&self, opts: &DiffOptions) -> Dmp {
+        Dmp {
+            diff_timeout: opts.diff_timeout.unwrap_or(self.diff_timeout),
+            diff_budget: opts.diff_budget.unwrap_or(self.diff_budget),
+            edit_cost: opts.edit_cost.unwrap_or(self.edit_cost),
+            match_distance: opts.match_distance.unwrap_or(self.match_distance),
+            patch_margin: opts.patch_margin.unwrap_or(self.patch_margin),
+            match_maxbits: opts.match_maxbits.unwrap_or(self.match_maxbits),
+            match_threshold: opts.match_threshold.unwrap_or(self.match_threshold),
+            patch_delete_threshold: opts
+                .patch_delete_threshold
+                .unwrap_or(self.patch_delete_threshold),
+            patch_apply_policy: opts.apply_policy.unwrap_or(self.patch_apply_policy),
+            segmentation: opts.segmentation.unwrap_or(self.segmentation),
+            word_mode: opts.word_mode.unwrap_or(self.word_mode),
+            word_vocabulary: opts.word_vocabulary.unwrap_or(self.word_vocabulary),
+            diff_algorithm: opts.algorithm.unwrap_or(self.diff_algorithm),
+        }
+    }
+
+    /// The deadline and budget of `dmp` plus the cancel token and progress
+    /// hook of `opts`.
+    fn limits<'a>(dmp: &Dmp, opts: &'a DiffOptions) -> Limits<'a> {
+        Limits::from_dmp(dmp)
+            .with_cancel(opts.cancel.as_ref())
+            .with_progress(opts.progress.as_ref())
+    }
+
+    /// `diff_main` with per-call settings, cancel token and progress hook.
+    ///
+    /// Args:
+    /// opts: Overrides for this call.
+    /// text1: Old string to be diffed.
+    /// text2: New string to be diffed.
+    ///
+    /// Returns:
+    /// Vector of diffs as changes.
+    pub fn diff_main_with(&self, opts: &DiffOptions, text1: &str, text2: &str) -> Vec<Diff> {
+        let dmp = self.with_overrides(opts);
+        let limits = Dmp::limits(&dmp, opts);
+        let checklines = opts.checklines.unwrap_or(true);
+        let diffs = crate::diff::diff_main_limited(&dmp, text1, text2, checklines, &limits);
+        limits.finish();
+        diffs
+    }
+
+    /// `diff_main` with per-call settings, honouring the options' cancel
+    /// token and progress hook, and reporting whether the diff was cut short
+    /// (by the timeout or by cancellation) along with run statistics.
+    ///
+    /// ```
+    /// use diff_match_patch::{CancelToken, DiffOptions, Dmp, Truncation};
+    ///
+    /// let token = CancelToken::new();
+    /// let opts = DiffOptions::new().checklines(false).cancel(token.clone());
+    /// token.cancel();
+    /// let outcome = Dmp::new().diff_main_outcome(&opts, "cat", "map");
+    /// assert_eq!(outcome.truncated, Some(Truncation::Cancelled));
+    /// // Still a valid diff, just not a minimal one.
+    /// assert_eq!(outcome.diffs.len(), 2);
+    /// ```
+    ///
+    /// Args:
+    /// opts: Overrides, cancel token and progress hook for this call.
+    /// text1: Old string to be diffed.
+    /// text2: New string to be diffed.
+    ///
+    /// Returns:
+    /// The diffs, whether they are exact or truncated, and how they were
+    /// computed.
+    pub fn diff_main_outcome(&self, opts: &DiffOptions, text1: &str, text2: &str) -> DiffOutcome {
+        let start = Instant::now();
+        let dmp = self.with_overrides(opts);
+        let limits = Dmp::limits(&dmp, opts);
+        let checklines = opts.checklines.unwrap_or(true);
+        let diffs = crate::diff::diff_main_limited(&dmp, text1, text2, checklines, &limits);
+        limits.finish();
+        limits.record(|s| s.elapsed = start.elapsed());
+        DiffOutcome {
+            diffs,
+            truncated: limits.truncated(),
+            stats: limits.stats(),
+        }
+    }
+
+    /// `diff_words` with per-call settings, cancel token and progress hook.
+    ///
+    /// Args:
+    /// opts: Overrides for this call.
+    /// old: Old string to be diffed.
+    /// new: New string to be diffed.
+    ///
+    /// Returns:
+    /// Vector of diffs as changes.
+    pub fn diff_words_with(&self, opts: &DiffOptions, old: &str, new: &str) -> Vec<Diff> {
+        let dmp = self.with_overrides(opts);
+        let limits = Dmp::limits(&dmp, opts);
+        let diffs = crate::diff::diff_words_limited(&dmp, old, new, &limits);
+        limits.finish();
+        diffs
+    }
+
+    /// `match_main` with per-call settings.
+    ///
+    /// Args:
+    /// opts: Overrides for this call.
+    /// text: The text to search.
+    /// pattern: The pattern to search for.
+    /// loc: The location to search around.
+    ///
+    /// Returns:
+    /// Best match index or -1.
+    pub fn match_main_with(&self, opts: &DiffOptions, text: &str, pattern: &str, loc: i32) -> i32 {
+        self.with_overrides(opts).match_main(text, pattern, loc)
+    }
+
+    /// `patch_make1` with per-call settings, cancel token and progress hook.
+    ///
+    /// Args:
+    /// opts: Overrides for this call.
+    /// text1: Old text.
+    /// text2: New text.
+    ///
+    /// Returns:
+    /// Vector of Patch objects.
+    pub fn patch_make_with(&self, opts: &DiffOptions, text1: &str, text2: &str) -> Vec<Patch> {
+        let dmp = self.with_overrides(opts);
+        let limits = Dmp::limits(&dmp, opts);
+        let patches = crate::patch::patch_make_limited(&dmp, text1, text2, &limits);
+        limits.finish();
+        patches
+    }
+
+    /// `patch_apply` with per-call settings. The patches are only read.
+    ///
+    /// Args:
+    /// opts: Overrides for this call.
+    /// patches: Vector of Patch objects.
+    /// text: Old text.
+    ///
+    /// Returns:
+    /// The new text, and whether each patch applied.
+    pub fn patch_apply_with(
+        &self,
+        opts: &DiffOptions,
+        patches: &[Patch],
+        text: &str,
+    ) -> (String, Vec<bool>) {
+        self.with_overrides(opts).patch_apply_str(patches, text)
+    }
+}
@@ -0,0 +1,330 @@
+// Run control for a single diff: the wall-clock deadline, cooperative
+// cancellation and progress reporting, checked from inside the recursion,
+// plus the record of whether the result was cut short.
+
+use crate::types::{Diff, DiffToken, Dmp};
+use std::cell::{Cell, OnceCell};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A shared flag that asks running diffs to stop. Clones share the flag, so
+/// hand one clone to the diff (via [`crate::DiffOptions::cancel`]) and keep
+/// another to call [`CancelToken::cancel`] from any thread.
+///
+/// Cancellation is cooperative: the bisect checks the flag once per step and
+/// the line/word rediff before each block, so a cancelled diff returns
+/// promptly with the rest of the text as a coarse delete+insert, exactly as
+/// when `diff_timeout` expires.
+///
+/// Tokens compare equal when they are clones sharing one flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &CancelToken) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Ask every diff holding a clone of this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How far a diff has got, in bytes of both inputs together whose place in
+/// the result is settled: `bytes_total` is `text1.len() + text2.len()`
+/// whatever the diff runs on (chars, grapheme clusters or words), so it can
+/// drive a progress bar over the input. `bytes_done` only grows and ends
+/// equal to `bytes_total`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    pub bytes_done: usize,
+    pub bytes_total: usize,
+}
+
+/// A progress callback, shared by every clone of the options holding it.
+/// Hooks compare equal when they are clones of one callback.
+#[derive(Clone)]
+pub struct ProgressHook(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl PartialEq for ProgressHook {
+    fn eq(&self, other: &ProgressHook) -> bool {
+        // Data pointers only: vtable addresses are not unique.
+        std::ptr::eq(
+            Arc::as_ptr(&self.0) as *const (),
+            Arc::as_ptr(&other.0) as *const (),
+        )
+    }
+}
+
+impl ProgressHook {
+    pub fn new(hook: impl Fn(Progress) + Send + Sync + 'static) -> ProgressHook {
+        ProgressHook(Arc::new(hook))
+    }
+}
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
+
+/// Why a diff stopped refining before it was done.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Truncation {
+    /// `diff_timeout` expired.
+    Deadline,
+    /// `diff_budget` was spent.
+    Budget,
+    /// The [`CancelToken`] was cancelled.
+    Cancelled,
+}
+
+/// What a diff did to get its result. Counts cover the whole recursion,
+/// including the diffs over packed line or word ids.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiffStats {
+    /// `diff_timeout` expired during a bisect. A delete+insert pair in the
+    /// result may then stand for text that was never compared, rather than
+    /// text with nothing in common.
+    pub deadline_hit: bool,
+    /// Bisect searches started.
+    pub bisects: usize,
+    /// Bisect work spent, in the units of `Dmp::diff_budget`.
+    pub steps: u64,
+    /// Line-mode passes (the `checklines` speedup) run.
+    pub line_mode_passes: usize,
+    /// Word-mode passes (`Dmp::word_mode`) run.
+    pub word_mode_passes: usize,
+    /// Wall-clock time of the whole call.
+    pub elapsed: Duration,
+    /// Some of the text was diffed as bytes on the ASCII fast path.
+    pub ascii_fast_path: bool,
+    /// The texts were packed into grapheme cluster ids
+    /// (`Segmentation::Grapheme`).
+    pub grapheme_packed: bool,
+}
+
+/// A diff together with how it was computed.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffOutcome {
+    pub diffs: Vec<Diff>,
+    /// Set when some part of the text was left as a coarse delete+insert
+    /// because the diff was stopped; the first reason wins. The diffs are
+    /// valid either way (they rebuild both texts).
+    pub truncated: Option<Truncation>,
+    pub stats: DiffStats,
+}
+
+impl DiffOutcome {
+    /// True when the diff ran to completion. Speedups that trade minimality
+    /// for speed (line mode, half-match) still count as complete.
+    pub fn is_exact(&self) -> bool {
+        self.truncated.is_none()
+    }
+}
+
+/// Per-call state threaded through the diff recursion. Counters use `Cell`
+/// so the recursion can share one `&Limits`.
+pub(crate) struct Limits<'a> {
+    deadline: Option<Instant>,
+    budget: Option<u64>,
+    out_of_budget: Cell<bool>,
+    cancel: Option<&'a CancelToken>,
+    progress: Option<&'a ProgressHook>,
+    total: Cell<usize>,
+    /// Input bytes per placeholder char, when the diff runs on packed ids.
+    widths: OnceCell<Box<dyn Fn(char) -> usize>>,
+    settled: Cell<usize>,
+    reported: Cell<usize>,
+    muted: Cell<u32>,
+    truncated: Cell<Option<Truncation>>,
+    stats: Cell<DiffStats>,
+}
+
+impl<'a> Limits<'a> {
+    /// Just a deadline, no budget.
+    pub fn new(deadline: Option<Instant>) -> Limits<'a> {
+        Limits {
+            deadline,
+            budget: None,
+            out_of_budget: Cell::new(false),
+            cancel: None,
+            progress: None,
+            total: Cell::new(0),
+            widths: OnceCell::new(),
+            settled: Cell::new(0),
+            reported: Cell::new(0),
+            muted: Cell::new(0),
+            truncated: Cell::new(None),
+            stats: Cell::new(DiffStats::default()),
+        }
+    }
+
+    /// The deadline and budget of `dmp`: what every plain entry point runs
+    /// with.
+    pub fn from_dmp(dmp: &Dmp) -> Limits<'a> {
+        let mut limits = Limits::new(dmp.deadline_from_now());
+        limits.budget = dmp.diff_budget;
+        limits
+    }
+
+    pub fn with_cancel(mut self, cancel: Option<&'a CancelToken>) -> Limits<'a> {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn with_progress(mut self, hook: Option<&'a ProgressHook>) -> Limits<'a> {
+        self.progress = hook;
+        self
+    }
+
+    /// Set the progress total: the byte length of both inputs.
+    pub fn begin(&self, total: usize) {
+        self.total.set(total);
+    }
+
+    /// `begin` for a diff over placeholder chars; `widths` gives the input
+    /// bytes behind each and only runs when someone is listening.
+    pub fn begin_packed<W: Fn(char) -> usize + 'static>(
+        &self,
+        total: usize,
+        widths: impl FnOnce() -> W,
+    ) {
+        self.begin(total);
+        if self.progress.is_some() {
+            let _ = self.widths.set(Box::new(widths()));
+        }
+    }
+
+    /// Whether a deadline or budget is set at all (either gates the
+    /// half-match speedup).
+    pub fn is_bounded(&self) -> bool {
+        self.deadline.is_some() || self.budget.is_some()
+    }
+
+    /// Checked before each step of `cost` budget units (a bisect step's
+    /// diagonals, a histogram search's lines): cancelled, out of budget (the
+    /// step must fit in what is left), or past the deadline.
+    pub fn step_should_stop(&self, cost: u64) -> bool {
+        if self.rediff_should_stop() {
+            return true;
+        }
+        if let Some(budget) = self.budget {
+            let spent = self.stats.get().steps + cost;
+            if spent > budget {
+                self.out_of_budget.set(true);
+                self.stop(Truncation::Budget);
+                return true;
+            }
+        }
+        self.record(|s| s.steps += cost);
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                self.record(|s| s.deadline_hit = true);
+                self.stop(Truncation::Deadline);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Checked before each rediff block: cancelled, or the budget ran out
+    /// earlier. An expired deadline still lets the cheap trimming passes
+    /// run.
+    pub fn rediff_should_stop(&self) -> bool {
+        if self.cancel.is_some_and(CancelToken::is_cancelled) {
+            self.stop(Truncation::Cancelled);
+            return true;
+        }
+        self.out_of_budget.get()
+    }
+
+    fn stop(&self, reason: Truncation) {
+        if self.truncated.get().is_none() {
+            self.truncated.set(Some(reason));
+        }
+    }
+
+    pub fn truncated(&self) -> Option<Truncation> {
+        self.truncated.get()
+    }
+
+    /// Update the run's statistics.
+    pub fn record(&self, f: impl FnOnce(&mut DiffStats)) {
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+    }
+
+    /// The ASCII fast path ran on input text (packed ids do not count).
+    pub fn record_ascii(&self) {
+        if self.muted.get() == 0 {
+            self.record(|s| s.ascii_fast_path = true);
+        }
+    }
+
+    pub fn stats(&self) -> DiffStats {
+        self.stats.get()
+    }
+
+    /// Record tokens of `old` and `new` whose place in the result is final,
+    /// and report their bytes to the hook about once per percent.
+    pub fn settle<T: DiffToken>(&self, old: &[T], new: &[T]) {
+        if self.progress.is_none() || self.muted.get() > 0 {
+            return;
+        }
+        let width = |t: &T| match self.widths.get() {
+            Some(widths) => widths(t.as_char()),
+            None => t.as_char().len_utf8(),
+        };
+        let n: usize = old.iter().chain(new).map(width).sum();
+        let total = self.total.get();
+        let done = usize::min(self.settled.get() + n, total);
+        self.settled.set(done);
+        if done - self.reported.get() >= usize::max(total / 100, 1) {
+            self.report(done);
+        }
+    }
+
+    /// Final report: everything is settled.
+    pub fn finish(&self) {
+        if self.progress.is_some() && self.reported.get() < self.total.get() {
+            self.report(self.total.get());
+        }
+    }
+
+    fn report(&self, done: usize) {
+        self.reported.set(done);
+        if let Some(hook) = self.progress {
+            (hook.0)(Progress {
+                bytes_done: done,
+                bytes_total: self.total.get(),
+            });
+        }
+    }
+
+    /// Run `f` without settling progress: for diffs over packed tokens,
+    /// whose pieces are settled again when they are rehydrated.
+    pub fn muted<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.muted.set(self.muted.get() + 1);
+        let result = f();
+        self.muted.set(self.muted.get() - 1);
+        result
+    }
+}
@@ -1,12 +1,16 @@
 // Patch construction, splitting, padding, apply, and the patch text wire
 // format (Patch::to_string / patch_to_text / patch_from_text).
 
+use crate::apply::{ApplyFailure, ApplyMethod, ApplyPolicy, PatchReport};
 use crate::delta::encode_uri;
 use crate::engine;
-use crate::types::{max, min, Diff, Dmp, Patch};
+use crate::error::ParseError;
+use crate::outcome::Limits;
+use crate::types::{max, min, Diff, DiffToken, Dmp, Operation, Patch};
 use core::char;
 use percent_encoding::percent_decode;
 use std::fmt;
+use std::hash::Hash;
 
 // The historic public API takes &Vec/&mut Vec/&String; frozen by the
 // drop-in compatibility contract.
@@ -18,7 +22,7 @@ impl Dmp {
     /// Args:
     /// patch: The patch to grow.
     /// text: Source text.
-    pub fn patch_add_context(&mut self, patch: &mut Patch, text: &mut Vec<char>) {
+    pub fn patch_add_context(&self, patch: &mut Patch, text: &mut Vec<char>) {
         if text.is_empty() {
             return;
         }
@@ -59,7 +63,9 @@ impl Dmp {
             .collect();
         let prefix_length = prefix.chars().count() as i32;
         if !prefix.is_empty() {
-            patch.diffs.insert(0, Diff::new(0, prefix.clone()));
+            patch
+                .diffs
+                .insert(0, Diff::new(Operation::Equal, prefix.clone()));
         }
 
         // Add the suffix.
@@ -69,7 +75,7 @@ impl Dmp {
             .collect();
         let suffix_length = suffix.chars().count() as i32;
         if !suffix.is_empty() {
-            patch.diffs.push(Diff::new(0, suffix));
+            patch.diffs.push(Diff::new(Operation::Equal, suffix));
         }
         // Roll back the start points.
         patch.start1 -= prefix_length;
@@ -86,13 +92,8 @@ impl Dmp {
     /// text2: Second string.
     /// Returns:
     /// Vector of Patch objects.
-    pub fn patch_make1(&mut self, text1: &str, text2: &str) -> Vec<Patch> {
-        let mut diffs: Vec<Diff> = self.diff_main(text1, text2, true);
-        if diffs.len() > 2 {
-            self.diff_cleanup_semantic(&mut diffs);
-            self.diff_cleanup_efficiency(&mut diffs);
-        }
-        self.patch_make4(text1, &mut diffs)
+    pub fn patch_make1(&self, text1: &str, text2: &str) -> Vec<Patch> {
+        patch_make_limited(self, text1, text2, &Limits::from_dmp(self))
     }
 
     /// Compute a list of patches to turn text1 into text2.
@@ -102,7 +103,7 @@ impl Dmp {
     /// diffs: Vector od diff object.
     /// Returns:
     /// Vector of Patch objects.
-    pub fn patch_make2(&mut self, diffs: &mut Vec<Diff>) -> Vec<Patch> {
+    pub fn patch_make2(&self, diffs: &mut Vec<Diff>) -> Vec<Patch> {
         let text1 = self.diff_text1(diffs);
         self.patch_make4(text1.as_str(), diffs)
     }
@@ -116,7 +117,7 @@ impl Dmp {
     ///
     /// Returns:
     /// Vector of Patch objects.
-    pub fn patch_make3(&mut self, text1: &str, _text2: &str, diffs: &mut Vec<Diff>) -> Vec<Patch> {
+    pub fn patch_make3(&self, text1: &str, _text2: &str, diffs: &mut Vec<Diff>) -> Vec<Patch> {
         self.patch_make4(text1, diffs)
     }
     /// Compute a list of patches to turn text1 into text2.
@@ -126,7 +127,7 @@ impl Dmp {
     /// diffs: Vector of diff object.
     /// Returns:
     /// Array of Patch objects.
-    pub fn patch_make4(&mut self, text1: &str, diffs: &mut Vec<Diff>) -> Vec<Patch> {
+    pub fn patch_make4(&self, text1: &str, diffs: &mut Vec<Diff>) -> Vec<Patch> {
         let mut patches: Vec<Patch> = vec![];
         if diffs.is_empty() {
             return patches; // Get rid of the None case.
@@ -138,12 +139,12 @@ impl Dmp {
         let mut postpatch: Vec<char> = prepatch.clone();
         for i in 0..diffs.len() {
             let temp1: &Vec<char> = &(diffs[i].text.chars().collect());
-            if patch.diffs.is_empty() && diffs[i].operation != 0 {
+            if patch.diffs.is_empty() && diffs[i].operation != Operation::Equal {
                 // A new patch starts here.
                 patch.start1 = char_count1;
                 patch.start2 = char_count2;
             }
-            if diffs[i].operation == 1 {
+            if diffs[i].operation == Operation::Insert {
                 // Insertion
                 patch
                     .diffs
@@ -153,7 +154,7 @@ impl Dmp {
                     char_count2 as usize..char_count2 as usize,
                     temp1.iter().copied(),
                 );
-            } else if diffs[i].operation == -1 {
+            } else if diffs[i].operation == Operation::Delete {
                 // Deletion.
                 patch
                     .diffs
@@ -185,10 +186,10 @@ impl Dmp {
             }
 
             // Update the current character count.
-            if diffs[i].operation != 1 {
+            if diffs[i].operation != Operation::Insert {
                 char_count1 += temp1.len() as i32;
             }
-            if diffs[i].operation != -1 {
+            if diffs[i].operation != Operation::Delete {
                 char_count2 += temp1.len() as i32;
             }
         }
@@ -209,7 +210,7 @@ impl Dmp {
     ///
     /// Returns:
     /// Vector of Patch objects.
-    pub fn patch_deep_copy(&mut self, patches: &mut Vec<Patch>) -> Vec<Patch> {
+    pub fn patch_deep_copy(&self, patches: &mut Vec<Patch>) -> Vec<Patch> {
         let mut patches_copy: Vec<Patch> = vec![];
         for patches_item in patches {
             let mut patch_copy = Patch::new(vec![], 0, 0, 0, 0);
@@ -230,13 +231,15 @@ impl Dmp {
     }
 
     pub fn patch_apply(
-        &mut self,
+        &self,
         patches: &mut Vec<Patch>,
         source_text: &str,
     ) -> (Vec<char>, Vec<bool>) {
         /*
           Merge a set of patches onto the text.  Return a patched text, as well
           as a list of true/false values indicating which patches were applied.
+          patch_apply_policy decides whether a patch may be found away from its
+          expected location, or in text that has changed.
 
           Args:
               patches: Vector of Patch objects.
@@ -246,130 +249,97 @@ impl Dmp {
               Two element Vector, containing the new chars and an Vector of boolean values.
         */
 
-        if patches.is_empty() {
-            return (source_text.chars().collect(), vec![]);
-        }
+        let (text, reports) = apply_reported(self, patches, source_text);
+        (text, reports.iter().map(PatchReport::applied).collect())
+    }
 
-        // Deep copy the patches so that no changes are made to originals.
-        let mut patches_copy: Vec<Patch> = self.patch_deep_copy(patches);
-
-        let null_padding: Vec<char> = self.patch_add_padding(&mut patches_copy);
-
-        let mut text = null_padding.clone();
-        text.extend(source_text.chars());
-        text.extend(&null_padding);
-
-        self.patch_splitmax(&mut patches_copy);
-
-        // delta keeps track of the offset between the expected and actual location
-        // of the previous patch.  If there are patches expected at positions 10 and
-        // 20, but the first patch was found at 12, delta is 2 and the second patch
-        // has an effective expected position of 22.
-        let mut delta: i32 = 0;
-        let mut results: Vec<bool> = vec![false; patches_copy.len()];
-        for x in 0..patches_copy.len() {
-            let expected_loc: i32 = patches_copy[x].start2 + delta;
-            let text1: Vec<char> = diff_text1_chars(&patches_copy[x].diffs);
-            let mut start_loc: i32;
-            let mut end_loc = -1;
-            if text1.len() as i32 > self.match_maxbits {
-                // patch_splitMax will only provide an oversized pattern in the case of
-                // a monster delete.
-                start_loc = crate::match_::match_chars(
-                    self,
-                    &text,
-                    &text1[..self.match_maxbits as usize],
-                    expected_loc,
-                );
-                if start_loc != -1 {
-                    end_loc = crate::match_::match_chars(
-                        self,
-                        &text,
-                        &text1[text1.len() - self.match_maxbits as usize..],
-                        expected_loc + text1.len() as i32 - self.match_maxbits,
-                    );
-                    if end_loc == -1 || start_loc >= end_loc {
-                        // Can't find valid trailing context.  Drop this patch.
-                        start_loc = -1;
-                    }
-                }
-            } else {
-                start_loc = crate::match_::match_chars(self, &text, &text1, expected_loc);
-            }
-            if start_loc == -1 {
-                // No match found.  :(
-                results[x] = false;
-                // Subtract the delta for this failed patch from subsequent patches.
-                delta -= patches_copy[x].length2 - patches_copy[x].length1;
-            } else {
-                // Found a match.  :)
-                results[x] = true;
-                delta = start_loc - expected_loc;
+    /// `patch_apply` for callers holding text as a `str`: the result is a
+    /// `String` directly, with no `Vec<char>` in between. On ASCII text and
+    /// patches the patches are applied to the bytes themselves. The patches
+    /// are only read.
+    ///
+    /// Args:
+    /// patches: Vector of Patch objects.
+    /// text: Old text.
+    ///
+    /// Returns:
+    /// The new text, and whether each patch applied.
+    pub fn patch_apply_str(&self, patches: &[Patch], text: &str) -> (String, Vec<bool>) {
+        let mut text = text.to_string();
+        let results = self.patch_apply_in_place(patches, &mut text);
+        (text, results)
+    }
 
-                let mut end_index: usize;
-                if end_loc == -1 {
-                    end_index = start_loc as usize + text1.len();
-                } else {
-                    end_index = (end_loc + self.match_maxbits) as usize;
-                }
-                end_index = std::cmp::min(text.len(), end_index);
-
-                if text1[..] == text[start_loc as usize..end_index] {
-                    // Perfect match, just splice the replacement text in.
-                    let replacement = diff_text2_chars(&patches_copy[x].diffs);
-                    text.splice(
-                        start_loc as usize..start_loc as usize + text1.len(),
-                        replacement,
-                    );
-                } else {
-                    // Imperfect match.
-                    // Run a diff to get a framework of equivalent indices.
-                    let mut diffs: Vec<Diff> = crate::diff::diff_main_chars(
-                        self,
-                        &text1,
-                        &text[start_loc as usize..end_index],
-                        false,
-                    );
-                    if text1.len() as i32 > self.match_maxbits
-                        && (self.diff_levenshtein(&diffs) as f32 / (text1.len() as f32)
-                            > self.patch_delete_threshold)
-                    {
-                        // The end points match, but the content is unacceptably bad.
-                        results[x] = false;
-                    } else {
-                        self.diff_cleanup_semantic_lossless(&mut diffs);
-                        let mut index1: i32 = 0;
-                        for y in 0..patches_copy[x].diffs.len() {
-                            let op = patches_copy[x].diffs[y].operation;
-                            let mod_len = patches_copy[x].diffs[y].text.chars().count() as i32;
-                            if op != 0 {
-                                let index2: i32 = self.diff_xindex(&diffs, index1);
-                                if op == 1 {
-                                    // Insertion
-                                    let at = (start_loc + index2) as usize;
-                                    text.splice(at..at, patches_copy[x].diffs[y].text.chars());
-                                } else if op == -1 {
-                                    // Deletion. diff_xindex is non-decreasing
-                                    // in loc, so the range never inverts.
-                                    let from = (start_loc + index2) as usize;
-                                    let until = (start_loc
-                                        + self.diff_xindex(&diffs, index1 + mod_len))
-                                        as usize;
-                                    text.splice(from..until, std::iter::empty());
-                                }
-                            }
-                            if op != -1 {
-                                index1 += mod_len;
-                            }
+    /// `patch_apply_str` that patches `text` itself, reusing its buffer.
+    ///
+    /// Args:
+    /// patches: Vector of Patch objects.
+    /// text: Text to patch.
+    ///
+    /// Returns:
+    /// Whether each patch applied.
+    pub fn patch_apply_in_place(&self, patches: &[Patch], text: &mut String) -> Vec<bool> {
+        apply_in_place(self, patches, text)
+            .iter()
+            .map(PatchReport::applied)
+            .collect()
+    }
+
+    /// Turn patches taking text A to text B into patches taking B back to A:
+    /// insertions and deletions trade places, as do the two sides' starts
+    /// and lengths, and the context is kept. Within each change the
+    /// deletion still comes before the insertion, as `patch_make` writes
+    /// it. Apply the result in the same order as the originals; as each
+    /// patch's starts count from the text the patches before it produce,
+    /// the inverse ones are moved back by what the earlier patches grew the
+    /// text.
+    ///
+    /// Args:
+    /// patches: Vector of Patch objects.
+    ///
+    /// Returns:
+    /// Vector of inverted Patch objects.
+    pub fn patch_invert(&self, patches: &[Patch]) -> Vec<Patch> {
+        // Net chars the patches so far added.
+        let mut growth = 0;
+        patches
+            .iter()
+            .map(|patch| {
+                let mut diffs: Vec<Diff> = Vec::with_capacity(patch.diffs.len());
+                // Start of the current run of changes in `diffs`.
+                let mut run = 0;
+                for diff in &patch.diffs {
+                    let operation = match diff.operation {
+                        Operation::Insert => Operation::Delete,
+                        Operation::Delete => Operation::Insert,
+                        Operation::Equal => {
+                            run = diffs.len() + 1;
+                            Operation::Equal
                         }
+                    };
+                    if operation == Operation::Delete {
+                        // Ahead of the run's insertions.
+                        let at = run
+                            + diffs[run..]
+                                .iter()
+                                .take_while(|d| d.operation == Operation::Delete)
+                                .count();
+                        diffs.insert(at, Diff::new(operation, diff.text.clone()));
+                    } else {
+                        diffs.push(Diff::new(operation, diff.text.clone()));
                     }
                 }
-            }
-        }
-        // Strip the padding off.
-        text.drain(..null_padding.len());
-        text.truncate(text.len() - null_padding.len());
-        (text, results)
+                let inverse = Patch::new(
+                    diffs,
+                    patch.start2 - growth,
+                    patch.start1 - growth,
+                    patch.length2,
+                    patch.length1,
+                );
+                growth += patch.length2 - patch.length1;
+                inverse
+            })
+            .collect()
     }
 
     /// Add some padding on text start and end so that edges can match
@@ -380,7 +350,7 @@ impl Dmp {
     ///
     /// Returns:
     /// The padding chars added to each side.
-    pub fn patch_add_padding(&mut self, patches: &mut Vec<Patch>) -> Vec<char> {
+    pub fn patch_add_padding(&self, patches: &mut Vec<Patch>) -> Vec<char> {
         let padding_length = self.patch_margin;
         let mut nullpadding: Vec<char> = vec![];
         for i in 0..padding_length {
@@ -396,9 +366,12 @@ impl Dmp {
         }
         let mut patch = patches[0].clone();
         let mut diffs = patch.diffs;
-        if diffs.is_empty() || diffs[0].operation != 0 {
+        if diffs.is_empty() || diffs[0].operation != Operation::Equal {
             // Add nullPadding equality.
-            diffs.insert(0, Diff::new(0, nullpadding.clone().iter().collect()));
+            diffs.insert(
+                0,
+                Diff::new(Operation::Equal, nullpadding.clone().iter().collect()),
+            );
             patch.start1 -= padding_length; // Should be 0.
             patch.start2 -= padding_length; // Should be 0.
             patch.length1 += padding_length;
@@ -423,9 +396,12 @@ impl Dmp {
         patches[0] = patch;
         patch = patches[patches.len() - 1].clone();
         diffs = patch.diffs;
-        if diffs.is_empty() || diffs[diffs.len() - 1].operation != 0 {
+        if diffs.is_empty() || diffs[diffs.len() - 1].operation != Operation::Equal {
             // Add nullPadding equality.
-            diffs.push(Diff::new(0, nullpadding.clone().iter().collect()));
+            diffs.push(Diff::new(
+                Operation::Equal,
+                nullpadding.clone().iter().collect(),
+            ));
             patch.length1 += padding_length;
             patch.length2 += padding_length;
         } else {
@@ -453,7 +429,7 @@ impl Dmp {
     ///
     /// Args:
     /// patches: Array of Patch objects.
-    pub fn patch_splitmax(&mut self, patches: &mut Vec<Patch>) {
+    pub fn patch_splitmax(&self, patches: &mut Vec<Patch>) {
         let patch_size = self.match_maxbits;
         if patch_size == 0 {
             return;
@@ -479,23 +455,24 @@ impl Dmp {
                 if !precontext.is_empty() {
                     patch.length1 = precontext.len() as i32;
                     patch.length2 = precontext.len() as i32;
-                    patch
-                        .diffs
-                        .push(Diff::new(0, precontext.clone().iter().collect()));
+                    patch.diffs.push(Diff::new(
+                        Operation::Equal,
+                        precontext.clone().iter().collect(),
+                    ));
                 }
                 while !bigpatch.diffs.is_empty() && patch.length1 < patch_size - self.patch_margin {
                     let diff_type = bigpatch.diffs[0].operation;
                     let mut diff_text: Vec<char> = bigpatch.diffs[0].text.chars().collect();
-                    if diff_type == 1 {
+                    if diff_type == Operation::Insert {
                         // Insertions are harmless.
                         patch.length2 += diff_text.len() as i32;
                         start2 += diff_text.len() as i32;
                         patch.diffs.push(bigpatch.diffs[0].clone());
                         bigpatch.diffs.remove(0);
                         empty = false;
-                    } else if diff_type == -1
+                    } else if diff_type == Operation::Delete
                         && patch.diffs.len() == 1
-                        && patch.diffs[0].operation == 0
+                        && patch.diffs[0].operation == Operation::Equal
                         && (diff_text.len() as i32) > 2 * patch_size
                     {
                         // This is a large deletion.  Let it pass in one chunk.
@@ -516,7 +493,7 @@ impl Dmp {
                             .to_vec();
                         patch.length1 += diff_text.len() as i32;
                         start1 += diff_text.len() as i32;
-                        if diff_type == 0 {
+                        if diff_type == Operation::Equal {
                             patch.length2 += diff_text.len() as i32;
                             start2 += diff_text.len() as i32;
                         } else {
@@ -552,12 +529,13 @@ impl Dmp {
                 if !postcontext.is_empty() {
                     patch.length1 += postcontext_len;
                     patch.length2 += postcontext_len;
-                    if !patch.diffs.is_empty() && patch.diffs[patch.diffs.len() - 1].operation == 0
+                    if !patch.diffs.is_empty()
+                        && patch.diffs[patch.diffs.len() - 1].operation == Operation::Equal
                     {
                         let len = patch.diffs.len();
                         patch.diffs[len - 1].text += postcontext.as_str();
                     } else {
-                        patch.diffs.push(Diff::new(0, postcontext));
+                        patch.diffs.push(Diff::new(Operation::Equal, postcontext));
                     }
                 }
                 if !empty {
@@ -576,7 +554,7 @@ impl Dmp {
     ///
     /// Returns:
     /// Text representation of patches.
-    pub fn patch_to_text(&mut self, patches: &mut Vec<Patch>) -> String {
+    pub fn patch_to_text(&self, patches: &mut Vec<Patch>) -> String {
         let mut text: String = "".to_string();
         for patches_item in patches {
             text += (patches_item.to_string()).as_str();
@@ -593,12 +571,18 @@ impl Dmp {
     /// Returns:
     /// Vector of Patch objects.
     ///
-    /// Panics on malformed patch text.
-    pub fn patch_from_text(&mut self, textline: String) -> Vec<Patch> {
+    /// Panics on malformed patch text; see `try_patch_from_text`.
+    pub fn patch_from_text(&self, textline: String) -> Vec<Patch> {
         try_patch_from_text(&textline).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    pub fn patch1_from_text(&mut self, textline: String) -> Patch {
+    /// `patch_from_text` that reports malformed input as a `ParseError`
+    /// (with the offending line's index) instead of panicking.
+    pub fn try_patch_from_text(&self, textline: &str) -> Result<Vec<Patch>, ParseError> {
+        try_patch_from_text(textline)
+    }
+
+    pub fn patch1_from_text(&self, textline: String) -> Patch {
         // Parse full patch text and return its FIRST patch (any further hunks
         // are ignored); panics on malformed input.
         try_patch_from_text(&textline)
@@ -609,44 +593,239 @@ impl Dmp {
     }
 }
 
-/// diff_text1 (equalities + deletions) as chars — patch_apply works entirely
-/// in char space, so the String round trip would be pure overhead.
-fn diff_text1_chars(diffs: &[Diff]) -> Vec<char> {
-    let mut text = Vec::new();
-    for diff in diffs {
-        if diff.operation != 1 {
-            text.extend(diff.text.chars());
+/// `patch_apply` over chars, reporting how each (split) patch fared.
+pub(crate) fn apply_reported(
+    dmp: &Dmp,
+    patches: &[Patch],
+    source_text: &str,
+) -> (Vec<char>, Vec<PatchReport>) {
+    let mut text: Vec<char> = source_text.chars().collect();
+    let reports = apply_tokens(dmp, patches, &mut text);
+    (text, reports)
+}
+
+/// `patch_apply` on a `String`, reusing its buffer. When the text, the
+/// patches and the padding are all ASCII the loop runs over the string's own
+/// bytes (a bijection with chars there, as in the diff recursion), so no
+/// scalar is decoded; otherwise it goes through chars.
+pub(crate) fn apply_in_place(dmp: &Dmp, patches: &[Patch], text: &mut String) -> Vec<PatchReport> {
+    let ascii = text.is_ascii()
+        && dmp.patch_margin < 128
+        && patches
+            .iter()
+            .all(|patch| patch.diffs.iter().all(|diff| diff.text.is_ascii()));
+    if ascii {
+        let mut bytes = std::mem::take(text).into_bytes();
+        let reports = apply_tokens(dmp, patches, &mut bytes);
+        *text = String::from_utf8(bytes).expect("ascii patches keep ascii text ascii");
+        reports
+    } else {
+        let mut chars: Vec<char> = text.chars().collect();
+        let reports = apply_tokens(dmp, patches, &mut chars);
+        *text = chars.into_iter().collect();
+        reports
+    }
+}
+
+/// The `patch_apply` loop over the tokens of `text`, patched in place.
+fn apply_tokens<T: DiffToken + Hash>(
+    dmp: &Dmp,
+    patches: &[Patch],
+    text: &mut Vec<T>,
+) -> Vec<PatchReport> {
+    if patches.is_empty() {
+        return vec![];
+    }
+
+    // Deep copy the patches so that no changes are made to originals.
+    let mut patches_copy: Vec<Patch> = patches.to_vec();
+
+    let null_padding: String = dmp
+        .patch_add_padding(&mut patches_copy)
+        .into_iter()
+        .collect();
+    let mut padding_tokens: Vec<T> = vec![];
+    T::extend_from_str(&mut padding_tokens, &null_padding);
+    let padding = padding_tokens.len() as i32;
+    text.splice(0..0, padding_tokens.iter().copied());
+    text.extend(&padding_tokens);
+
+    dmp.patch_splitmax(&mut patches_copy);
+
+    // delta keeps track of the offset between the expected and actual location
+    // of the previous patch.  If there are patches expected at positions 10 and
+    // 20, but the first patch was found at 12, delta is 2 and the second patch
+    // has an effective expected position of 22.
+    let mut delta: i32 = 0;
+    let mut reports: Vec<PatchReport> = Vec::with_capacity(patches_copy.len());
+    for patch in &patches_copy {
+        let expected_loc: i32 = patch.start2 + delta;
+        let text1: Vec<T> = diff_text1_tokens(&patch.diffs);
+        let mut report = PatchReport {
+            expected: expected_loc - padding,
+            actual: None,
+            delta: None,
+            score: None,
+            method: None,
+            failure: None,
+        };
+        let mut start_loc: i32;
+        let mut end_loc = -1;
+        let score: f32;
+        let exact_within = match dmp.patch_apply_policy {
+            ApplyPolicy::ExactAt => Some((0, ApplyFailure::NotAtLocation)),
+            ApplyPolicy::ExactWithin(n) => Some((n, ApplyFailure::NotWithinOffset)),
+            ApplyPolicy::Fuzzy => None,
+        };
+        if let Some((within, failure)) = exact_within {
+            // Verbatim copies of the whole old text only, so a match always
+            // takes the splice path below.
+            (start_loc, score) =
+                crate::match_::match_exact(dmp, text, &text1, expected_loc, within);
+            if start_loc == -1 {
+                report.failure = Some(failure);
+            }
+        } else if text1.len() as i32 > dmp.match_maxbits {
+            // patch_splitMax will only provide an oversized pattern in the case of
+            // a monster delete.
+            (start_loc, score) = crate::match_::match_chars(
+                dmp,
+                text,
+                &text1[..dmp.match_maxbits as usize],
+                expected_loc,
+            );
+            if start_loc != -1 {
+                end_loc = crate::match_::match_chars(
+                    dmp,
+                    text,
+                    &text1[text1.len() - dmp.match_maxbits as usize..],
+                    expected_loc + text1.len() as i32 - dmp.match_maxbits,
+                )
+                .0;
+                if end_loc == -1 || start_loc >= end_loc {
+                    // Can't find valid trailing context.  Drop this patch.
+                    start_loc = -1;
+                    report.failure = Some(ApplyFailure::BadTrailingContext);
+                }
+            }
+        } else {
+            (start_loc, score) = crate::match_::match_chars(dmp, text, &text1, expected_loc);
+        }
+        if start_loc == -1 {
+            // No match found.  :(
+            report.failure.get_or_insert(ApplyFailure::NoMatch);
+            // Subtract the delta for this failed patch from subsequent patches.
+            delta -= patch.length2 - patch.length1;
+            reports.push(report);
+            continue;
+        }
+        // Found a match.  :)
+        delta = start_loc - expected_loc;
+        report.actual = Some(start_loc - padding);
+        report.delta = Some(delta);
+        report.score = Some(score);
+
+        let mut end_index: usize;
+        if end_loc == -1 {
+            end_index = start_loc as usize + text1.len();
+        } else {
+            end_index = (end_loc + dmp.match_maxbits) as usize;
         }
+        end_index = std::cmp::min(text.len(), end_index);
+
+        if text1[..] == text[start_loc as usize..end_index] {
+            // Perfect match, just splice the replacement text in.
+            report.method = Some(ApplyMethod::Splice);
+            let replacement: Vec<T> = diff_text2_tokens(&patch.diffs);
+            text.splice(
+                start_loc as usize..start_loc as usize + text1.len(),
+                replacement,
+            );
+        } else {
+            // Imperfect match.
+            // Run a diff to get a framework of equivalent indices.
+            report.method = Some(ApplyMethod::Diff);
+            let mut diffs: Vec<Diff> = crate::diff::diff_main_chars(
+                dmp,
+                &T::to_tokens(&text1),
+                &T::to_tokens(&text[start_loc as usize..end_index]),
+                false,
+            );
+            if text1.len() as i32 > dmp.match_maxbits
+                && (dmp.diff_levenshtein(&diffs) as f32 / (text1.len() as f32)
+                    > dmp.patch_delete_threshold)
+            {
+                // The end points match, but the content is unacceptably bad.
+                report.failure = Some(ApplyFailure::OverDeleteThreshold);
+            } else {
+                dmp.diff_cleanup_semantic_lossless(&mut diffs);
+                let mut index1: i32 = 0;
+                for diff in &patch.diffs {
+                    let op = diff.operation;
+                    let mut tokens: Vec<T> = vec![];
+                    T::extend_from_str(&mut tokens, &diff.text);
+                    let mod_len = tokens.len() as i32;
+                    if op != Operation::Equal {
+                        let index2: i32 = dmp.diff_xindex(&diffs, index1);
+                        if op == Operation::Insert {
+                            // Insertion
+                            let at = (start_loc + index2) as usize;
+                            text.splice(at..at, tokens);
+                        } else if op == Operation::Delete {
+                            // Deletion. diff_xindex is non-decreasing
+                            // in loc, so the range never inverts.
+                            let from = (start_loc + index2) as usize;
+                            let until =
+                                (start_loc + dmp.diff_xindex(&diffs, index1 + mod_len)) as usize;
+                            text.splice(from..until, std::iter::empty());
+                        }
+                    }
+                    if op != Operation::Delete {
+                        index1 += mod_len;
+                    }
+                }
+            }
+        }
+        reports.push(report);
     }
-    text
+    // Strip the padding off.
+    text.drain(..padding as usize);
+    text.truncate(text.len() - padding as usize);
+    reports
 }
 
-/// diff_text2 (equalities + insertions) as chars; see diff_text1_chars.
-fn diff_text2_chars(diffs: &[Diff]) -> Vec<char> {
+/// diff_text1 (equalities + deletions) as tokens — patch_apply works
+/// entirely in token space, so the String round trip would be pure overhead.
+pub(crate) fn diff_text1_tokens<T: DiffToken>(diffs: &[Diff]) -> Vec<T> {
     let mut text = Vec::new();
     for diff in diffs {
-        if diff.operation != -1 {
-            text.extend(diff.text.chars());
+        if diff.operation != Operation::Insert {
+            T::extend_from_str(&mut text, &diff.text);
         }
     }
     text
 }
 
-#[derive(Debug)]
-pub(crate) struct PatchError(String);
-
-impl fmt::Display for PatchError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+/// diff_text2 (equalities + insertions) as tokens; see diff_text1_tokens.
+fn diff_text2_tokens<T: DiffToken>(diffs: &[Diff]) -> Vec<T> {
+    let mut text = Vec::new();
+    for diff in diffs {
+        if diff.operation != Operation::Delete {
+            T::extend_from_str(&mut text, &diff.text);
+        }
     }
+    text
 }
 
 /// One header coordinate pair, with the oracle's exact semantics: "N" means
 /// start N-1 length 1, "N,0" keeps the raw start with length 0, "N,L" means
 /// start N-1 length L. Only plain digit runs are accepted, like the oracle's
 /// `\d+` (no sign, no overflow wrap).
-fn parse_coords(part: &str) -> Result<(i32, i32), PatchError> {
-    let err = || PatchError(format!("Invalid patch coordinates: {}", part));
+pub(crate) fn parse_coords(index: usize, part: &str) -> Result<(i32, i32), ParseError> {
+    let err = || ParseError::BadCoordinates {
+        index,
+        text: part.to_string(),
+    };
     let num = |s: &str| {
         if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
             return Err(err());
@@ -661,19 +840,37 @@ fn parse_coords(part: &str) -> Result<(i32, i32), PatchError> {
     }
 }
 
-fn parse_header(line: &str) -> Result<(i32, i32, i32, i32), PatchError> {
-    let err = || PatchError(format!("Invalid patch string: {}", line));
+fn parse_header(index: usize, line: &str) -> Result<(i32, i32, i32, i32), ParseError> {
+    let err = || ParseError::BadHeader {
+        index,
+        text: line.to_string(),
+    };
     let coords = line
         .strip_prefix("@@ -")
         .and_then(|r| r.strip_suffix(" @@"))
         .ok_or_else(err)?;
     let (c1, c2) = coords.split_once(" +").ok_or_else(err)?;
-    let (start1, length1) = parse_coords(c1)?;
-    let (start2, length2) = parse_coords(c2)?;
+    let (start1, length1) = parse_coords(index, c1)?;
+    let (start2, length2) = parse_coords(index, c2)?;
     Ok((start1, length1, start2, length2))
 }
 
-pub(crate) fn try_patch_from_text(text: &str) -> Result<Vec<Patch>, PatchError> {
+pub(crate) fn try_patch_from_text(text: &str) -> Result<Vec<Patch>, ParseError> {
+    parse_patch_text(text, |body| {
+        percent_decode(body.as_bytes())
+            .decode_utf8()
+            .ok()
+            .map(|t| t.to_string())
+    })
+}
+
+/// The patch text parser, with the body escape decoding supplied by the
+/// caller (UTF-8 text, or one char per byte for byte patches); `None` from
+/// `decode` is an illegal escape.
+pub(crate) fn parse_patch_text(
+    text: &str,
+    decode: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<Patch>, ParseError> {
     let mut patches: Vec<Patch> = vec![];
     let lines: Vec<&str> = text.split('\n').collect();
     let mut i = 0;
@@ -682,7 +879,7 @@ pub(crate) fn try_patch_from_text(text: &str) -> Result<Vec<Patch>, PatchError>
             i += 1;
             continue;
         }
-        let (start1, length1, start2, length2) = parse_header(lines[i])?;
+        let (start1, length1, start2, length2) = parse_header(i, lines[i])?;
         let mut patch = Patch::new(vec![], start1, start2, length1, length2);
         i += 1;
         while i < lines.len() {
@@ -699,15 +896,20 @@ pub(crate) fn try_patch_from_text(text: &str) -> Result<Vec<Patch>, PatchError>
             let mut line_chars = line.chars();
             let sign = line_chars.next().unwrap();
             let body = line_chars.as_str();
-            let decoded = percent_decode(body.as_bytes())
-                .decode_utf8()
-                .map_err(|_| PatchError(format!("Illegal escape in patch_from_text: {}", body)))?
-                .to_string();
+            let decoded = decode(body).ok_or_else(|| ParseError::IllegalEscape {
+                index: i,
+                text: body.to_string(),
+            })?;
             match sign {
-                '+' => patch.diffs.push(Diff::new(1, decoded)),
-                '-' => patch.diffs.push(Diff::new(-1, decoded)),
-                ' ' => patch.diffs.push(Diff::new(0, decoded)),
-                _ => return Err(PatchError(format!("Invalid patch mode: {}", line))),
+                '+' => patch.diffs.push(Diff::new(Operation::Insert, decoded)),
+                '-' => patch.diffs.push(Diff::new(Operation::Delete, decoded)),
+                ' ' => patch.diffs.push(Diff::new(Operation::Equal, decoded)),
+                _ => {
+                    return Err(ParseError::InvalidMode {
+                        index: i,
+                        text: line.to_string(),
+                    })
+                }
             }
             i += 1;
         }
@@ -721,27 +923,37 @@ pub(crate) fn try_patch_from_text(text: &str) -> Result<Vec<Patch>, PatchError>
 /// call sites keep working through the blanket `ToString`.
 impl fmt::Display for Patch {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let coords = |start: i32, length: i32| match length {
-            0 => format!("{},0", start),
-            1 => format!("{}", start + 1),
-            _ => format!("{},{}", start + 1, length),
+        write_patch(self, encode_uri, f)
+    }
+}
+
+/// The patch text writer, with the body escaping supplied by the caller (see
+/// `parse_patch_text`).
+pub(crate) fn write_patch(
+    patch: &Patch,
+    encode: impl Fn(&str) -> String,
+    f: &mut impl fmt::Write,
+) -> fmt::Result {
+    let coords = |start: i32, length: i32| match length {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, length),
+    };
+    writeln!(
+        f,
+        "@@ -{} +{} @@",
+        coords(patch.start1, patch.length1),
+        coords(patch.start2, patch.length2)
+    )?;
+    for diff in &patch.diffs {
+        let sign = match diff.operation {
+            Operation::Equal => ' ',
+            Operation::Delete => '-',
+            Operation::Insert => '+',
         };
-        writeln!(
-            f,
-            "@@ -{} +{} @@",
-            coords(self.start1, self.length1),
-            coords(self.start2, self.length2)
-        )?;
-        for diff in &self.diffs {
-            let sign = match diff.operation {
-                0 => ' ',
-                -1 => '-',
-                _ => '+',
-            };
-            writeln!(f, "{}{}", sign, encode_uri(&diff.text))?;
-        }
-        Ok(())
+        writeln!(f, "{}{}", sign, encode(&diff.text))?;
     }
+    Ok(())
 }
 
 // The historic public API takes &Vec/&mut Vec/&String; frozen by the
@@ -756,7 +968,7 @@ impl Dmp {
     ///
     /// Returns:
     /// Vector of string after spliting according to character.
-    pub fn split_by_char(&mut self, text: &str, ch: char) -> Vec<String> {
+    pub fn split_by_char(&self, text: &str, ch: char) -> Vec<String> {
         let temp: Vec<&str> = text.split(ch).collect();
         let mut temp1: Vec<String> = vec![];
         for temp_item in &temp {
@@ -772,7 +984,7 @@ impl Dmp {
     ///
     /// Returns:
     /// Vector of string after spliting according to characters.
-    pub fn split_by_chars(&mut self, text: &str) -> Vec<String> {
+    pub fn split_by_chars(&self, text: &str) -> Vec<String> {
         let temp: Vec<&str> = text.split("@@ ").collect();
         let mut temp1: Vec<String> = vec![];
         for temp_item in &temp {
@@ -781,3 +993,18 @@ impl Dmp {
         temp1
     }
 }
+
+/// `patch_make1` with the diff run under caller-built limits.
+pub(crate) fn patch_make_limited(
+    dmp: &Dmp,
+    text1: &str,
+    text2: &str,
+    limits: &Limits,
+) -> Vec<Patch> {
+    let mut diffs = crate::diff::diff_main_limited(dmp, text1, text2, true, limits);
+    if diffs.len() > 2 {
+        dmp.diff_cleanup_semantic(&mut diffs);
+        dmp.diff_cleanup_efficiency(&mut diffs);
+    }
+    dmp.patch_make4(text1, &mut diffs)
+}
@@ -0,0 +1,312 @@
+// Human-facing diff renderers: the reference's diff_prettyHtml and its
+// configurable variant, plus inline text renderers for terminals and logs
+// (ANSI colors, wdiff's [-deleted-]{+inserted+} notation).
+
+use crate::types::{Diff, Dmp, Operation};
+use std::fmt;
+
+/// Markup choices for [`Dmp::diff_pretty_html_with`]. The default renders
+/// byte-for-byte what the reference `diff_prettyHtml` does.
+///
+/// Tags must be plain element names (an ASCII letter, then ASCII letters,
+/// digits or `-`), or rendering fails with [`MarkupError`]. Classes are
+/// attribute values and are escaped, so any text is safe there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HtmlOptions {
+    /// Element wrapping each deletion.
+    pub delete_tag: String,
+    /// Element wrapping each equality.
+    pub equal_tag: String,
+    /// Element wrapping each insertion.
+    pub insert_tag: String,
+    /// `class` attribute for deletions. `None` keeps the reference's inline
+    /// `style` background instead.
+    pub delete_class: Option<String>,
+    /// `class` attribute for equalities (the reference sets none).
+    pub equal_class: Option<String>,
+    /// `class` attribute for insertions. `None` keeps the reference's inline
+    /// `style` background instead.
+    pub insert_class: Option<String>,
+    /// Mark each newline with a pilcrow (`&para;<br>`, the reference) rather
+    /// than a bare `<br>`.
+    pub pilcrow: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        HtmlOptions {
+            delete_tag: "del".to_string(),
+            equal_tag: "span".to_string(),
+            insert_tag: "ins".to_string(),
+            delete_class: None,
+            equal_class: None,
+            insert_class: None,
+            pilcrow: true,
+        }
+    }
+}
+
+/// Why [`Dmp::diff_pretty_html_with`] refused its options.
+#[non_exhaustive]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MarkupError {
+    /// A tag that is not a plain element name, so it could break out of
+    /// the markup (`<`, `>`, quotes, whitespace, ...).
+    InvalidTag { tag: String },
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MarkupError::InvalidTag { tag } => write!(f, "Invalid HTML tag name: {:?}", tag),
+        }
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+/// Presentation choices for the inline text renderers
+/// ([`Dmp::diff_pretty_ansi`], [`Dmp::diff_pretty_wdiff`]). The default
+/// prints every chunk verbatim.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InlineOptions {
+    /// Make whitespace inside deleted and inserted text visible: `·` for a
+    /// space, `→` for a tab, and `↵` ahead of each newline (the line break
+    /// itself is kept), so whitespace-only edits show up.
+    pub show_whitespace: bool,
+    /// Print the other C0 controls and DEL as their Unicode control pictures
+    /// (ESC becomes `␛`) throughout the output, so diffed text can never
+    /// drive the terminal.
+    pub show_control: bool,
+    /// Shorten each equality to this many chars of context on each side
+    /// that borders an edit, marking the cut with `…`. An equality that
+    /// borders no edit (an unchanged text) is kept whole, as is any with
+    /// `None`.
+    pub context: Option<usize>,
+}
+
+const ANSI_DELETE: &str = "\x1b[31m";
+const ANSI_INSERT: &str = "\x1b[32m";
+const ANSI_DIM: &str = "\x1b[2m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+impl Dmp {
+    /// Convert a diff into a pretty HTML report, byte-compatible with the
+    /// reference `diff_prettyHtml`: `&`, `<` and `>` are escaped and each
+    /// newline renders as `&para;<br>`.
+    ///
+    /// Args:
+    /// diffs: Vector of diff object.
+    ///
+    /// Returns:
+    /// HTML representation.
+    pub fn diff_pretty_html(&self, diffs: &[Diff]) -> String {
+        self.diff_pretty_html_with(diffs, &HtmlOptions::default())
+            .expect("default tags are valid")
+    }
+
+    /// `diff_pretty_html` with caller-chosen tags, classes and newline
+    /// marker.
+    ///
+    /// Args:
+    /// diffs: Vector of diff object.
+    /// options: Markup to emit.
+    ///
+    /// Returns:
+    /// HTML representation, or `MarkupError::InvalidTag` if a tag is not a
+    /// plain element name.
+    pub fn diff_pretty_html_with(
+        &self,
+        diffs: &[Diff],
+        options: &HtmlOptions,
+    ) -> Result<String, MarkupError> {
+        for tag in [&options.delete_tag, &options.equal_tag, &options.insert_tag] {
+            let mut chars = tag.chars();
+            let plain = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '-');
+            if !plain {
+                return Err(MarkupError::InvalidTag { tag: tag.clone() });
+            }
+        }
+        let newline = if options.pilcrow {
+            "&para;<br>"
+        } else {
+            "<br>"
+        };
+        let mut html = String::new();
+        for diff in diffs {
+            let (tag, class, style) = match diff.operation {
+                Operation::Insert => (
+                    &options.insert_tag,
+                    &options.insert_class,
+                    "background:#e6ffe6;",
+                ),
+                Operation::Delete => (
+                    &options.delete_tag,
+                    &options.delete_class,
+                    "background:#ffe6e6;",
+                ),
+                Operation::Equal => (&options.equal_tag, &options.equal_class, ""),
+            };
+            html.push('<');
+            html.push_str(tag);
+            match class {
+                Some(class) => {
+                    html.push_str(" class=\"");
+                    escape_html(class, ('"', "&quot;"), &mut html);
+                    html.push('"');
+                }
+                None if !style.is_empty() => {
+                    html.push_str(" style=\"");
+                    html.push_str(style);
+                    html.push('"');
+                }
+                None => {}
+            }
+            html.push('>');
+            escape_html(&diff.text, ('\n', newline), &mut html);
+            html.push_str("</");
+            html.push_str(tag);
+            html.push('>');
+        }
+        Ok(html)
+    }
+
+    /// Render a diff for a terminal: deletions red, insertions green,
+    /// elided context dimmed. Colors are closed before and reopened after
+    /// every line break inside a chunk, so line-oriented pagers keep them.
+    ///
+    /// Args:
+    /// diffs: Vector of diff object.
+    /// options: Whitespace, control char and context handling.
+    ///
+    /// Returns:
+    /// Text with ANSI SGR escapes.
+    pub fn diff_pretty_ansi(&self, diffs: &[Diff], options: &InlineOptions) -> String {
+        render_inline(diffs, options, true)
+    }
+
+    /// Render a diff in wdiff notation: `[-deleted-]` and `{+inserted+}`
+    /// around the edits, equalities as-is. A `-]` inside deleted text is
+    /// written `-\]` and a `+}` inside inserted text `+\}`, so no edit
+    /// closes early.
+    ///
+    /// Args:
+    /// diffs: Vector of diff object.
+    /// options: Whitespace, control char and context handling.
+    ///
+    /// Returns:
+    /// Plain text.
+    pub fn diff_pretty_wdiff(&self, diffs: &[Diff], options: &InlineOptions) -> String {
+        render_inline(diffs, options, false)
+    }
+}
+
+/// Shared walk of the inline renderers; `ansi` picks colors over wdiff
+/// brackets.
+fn render_inline(diffs: &[Diff], options: &InlineOptions, ansi: bool) -> String {
+    let mut out = String::new();
+    for (i, diff) in diffs.iter().enumerate() {
+        match diff.operation {
+            Operation::Equal => {
+                let text = visible(&diff.text, false, options);
+                let n = match options.context {
+                    Some(n) => n,
+                    None => {
+                        out.push_str(&text);
+                        continue;
+                    }
+                };
+                // Only sides that border an edit keep context; an equality
+                // bordering none (an unchanged text) is not shortened.
+                let edit = |d: Option<&Diff>| d.is_some_and(|d| d.operation != Operation::Equal);
+                let after = edit(i.checked_sub(1).and_then(|j| diffs.get(j)));
+                let before = edit(diffs.get(i + 1));
+                let head = if after { n } else { 0 };
+                let tail = if before { n } else { 0 };
+                let chars: Vec<char> = text.chars().collect();
+                if !(after || before) || head + tail >= chars.len() {
+                    out.push_str(&text);
+                    continue;
+                }
+                out.extend(&chars[..head]);
+                if ansi {
+                    out.push_str(ANSI_DIM);
+                    out.push('…');
+                    out.push_str(ANSI_RESET);
+                } else {
+                    out.push('…');
+                }
+                out.extend(&chars[chars.len() - tail..]);
+            }
+            op => {
+                let text = visible(&diff.text, true, options);
+                if ansi {
+                    let color = if op == Operation::Delete {
+                        ANSI_DELETE
+                    } else {
+                        ANSI_INSERT
+                    };
+                    for line in text.split_inclusive('\n') {
+                        let (body, newline) = match line.strip_suffix('\n') {
+                            Some(body) => (body, "\n"),
+                            None => (line, ""),
+                        };
+                        if !body.is_empty() {
+                            out.push_str(color);
+                            out.push_str(body);
+                            out.push_str(ANSI_RESET);
+                        }
+                        out.push_str(newline);
+                    }
+                } else if op == Operation::Delete {
+                    out.push_str("[-");
+                    out.push_str(&text.replace("-]", "-\\]"));
+                    out.push_str("-]");
+                } else {
+                    out.push_str("{+");
+                    out.push_str(&text.replace("+}", "+\\}"));
+                    out.push_str("+}");
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Apply the `show_whitespace` (edits only) and `show_control` mappings.
+fn visible(text: &str, edit: bool, options: &InlineOptions) -> String {
+    if !options.show_whitespace && !options.show_control {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            ' ' if edit && options.show_whitespace => out.push('·'),
+            '\t' if edit && options.show_whitespace => out.push('→'),
+            '\n' if edit && options.show_whitespace => out.push_str("↵\n"),
+            '\t' | '\n' => out.push(ch),
+            '\0'..='\x1f' if options.show_control => {
+                out.push(char::from_u32(0x2400 + ch as u32).expect("control picture"))
+            }
+            '\x7f' if options.show_control => out.push('␡'),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Append `text` with `&`, `<` and `>` escaped, exactly as the reference
+/// does, plus the one other char that needs it in context: `\n` in element
+/// content, `"` inside an attribute value.
+fn escape_html(text: &str, extra: (char, &str), out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            c if c == extra.0 => out.push_str(extra.1),
+            c => out.push(c),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// Generic sequence diff: the diff_main recursion (prefix/suffix trimming,
+// containment, half-match, Myers bisect) over caller-supplied `&[T]`, for
+// records, AST nodes or JSON array elements that are not text. Works on index
+// ranges and copies items only when building the result.
+
+use crate::engine;
+use crate::outcome::Limits;
+use crate::types::{Dmp, Operation};
+use std::hash::Hash;
+use std::ops::Range;
+
+/// One run of a sequence diff: the item counterpart of [`crate::Diff`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SeqDiff<T> {
+    pub operation: Operation,
+    pub items: Vec<T>,
+}
+
+impl<T> SeqDiff<T> {
+    /// Make a new sequence diff run.
+    pub fn new(operation: Operation, items: Vec<T>) -> SeqDiff<T> {
+        SeqDiff { operation, items }
+    }
+}
+
+impl Dmp {
+    /// Find the differences between two item sequences, with the same
+    /// algorithm `diff_main` runs on chars: common prefix and suffix are
+    /// trimmed, containment and half-match splits are tried (half-match only
+    /// while `diff_timeout` is set), and the rest goes to the Myers bisect,
+    /// which gives up at the timeout with a coarse delete+insert. Adjacent
+    /// runs of one kind are merged and a deletion always precedes the
+    /// insertion it pairs with; no other cleanup is applied.
+    ///
+    /// Args:
+    /// old: Old items to be diffed.
+    /// new: New items to be diffed.
+    ///
+    /// Returns:
+    /// Vector of runs as changes.
+    pub fn diff_slices<T: Clone + Eq + Hash>(&self, old: &[T], new: &[T]) -> Vec<SeqDiff<T>> {
+        let limits = Limits::from_dmp(self);
+        let mut ops: Vec<(Operation, Range<usize>)> = vec![];
+        seq_main(old, new, 0, 0, &limits, &mut Vec::new(), &mut ops);
+        merge_ranges(ops)
+            .into_iter()
+            .map(|(op, range)| {
+                let items = if op == Operation::Insert {
+                    &new[range]
+                } else {
+                    &old[range]
+                };
+                SeqDiff::new(op, items.to_vec())
+            })
+            .collect()
+    }
+}
+
+/// The `main_slices` counterpart: emits runs as ranges of `old` (equal and
+/// delete) or `new` (insert), offset by where the slices sit in the inputs.
+fn seq_main<T: Eq>(
+    old: &[T],
+    new: &[T],
+    off1: usize,
+    off2: usize,
+    limits: &Limits,
+    scratch: &mut Vec<i32>,
+    out: &mut Vec<(Operation, Range<usize>)>,
+) {
+    let prefix = engine::common_prefix(old, new);
+    let suffix = engine::common_suffix(&old[prefix..], &new[prefix..]);
+    if prefix > 0 {
+        out.push((Operation::Equal, off1..off1 + prefix));
+    }
+    seq_compute(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+        off1 + prefix,
+        off2 + prefix,
+        limits,
+        scratch,
+        out,
+    );
+    if suffix > 0 {
+        out.push((
+            Operation::Equal,
+            off1 + old.len() - suffix..off1 + old.len(),
+        ));
+    }
+}
+
+/// The `compute` counterpart, for slices with no common prefix or suffix.
+fn seq_compute<T: Eq>(
+    old: &[T],
+    new: &[T],
+    off1: usize,
+    off2: usize,
+    limits: &Limits,
+    scratch: &mut Vec<i32>,
+    out: &mut Vec<(Operation, Range<usize>)>,
+) {
+    let delete = (Operation::Delete, off1..off1 + old.len());
+    let insert = (Operation::Insert, off2..off2 + new.len());
+    if old.is_empty() || new.is_empty() {
+        out.push(delete);
+        out.push(insert);
+        return;
+    }
+    if old.len() >= new.len() {
+        if let Some(i) = engine::contains(old, new) {
+            // New is inside old (speedup).
+            out.push((Operation::Delete, off1..off1 + i));
+            out.push((Operation::Equal, off1 + i..off1 + i + new.len()));
+            out.push((Operation::Delete, off1 + i + new.len()..off1 + old.len()));
+            return;
+        }
+    } else if let Some(i) = engine::contains(new, old) {
+        // Old is inside new (speedup).
+        out.push((Operation::Insert, off2..off2 + i));
+        out.push((Operation::Equal, off1..off1 + old.len()));
+        out.push((Operation::Insert, off2 + i + old.len()..off2 + new.len()));
+        return;
+    }
+    if old.len() == 1 || new.len() == 1 {
+        // After the containment check, a single item can't be an equality.
+        out.push(delete);
+        out.push(insert);
+        return;
+    }
+
+    if limits.is_bounded() {
+        if let Some(hm) = engine::half_match(old, new) {
+            seq_main(
+                &old[..hm.old_a],
+                &new[..hm.new_a],
+                off1,
+                off2,
+                limits,
+                scratch,
+                out,
+            );
+            out.push((
+                Operation::Equal,
+                off1 + hm.old_a..off1 + hm.old_a + hm.common,
+            ));
+            seq_main(
+                &old[hm.old_a + hm.common..],
+                &new[hm.new_a + hm.common..],
+                off1 + hm.old_a + hm.common,
+                off2 + hm.new_a + hm.common,
+                limits,
+                scratch,
+                out,
+            );
+            return;
+        }
+    }
+
+    match engine::bisect(old, new, limits, scratch) {
+        Some((x, y)) => {
+            seq_main(&old[..x], &new[..y], off1, off2, limits, scratch, out);
+            seq_main(
+                &old[x..],
+                &new[y..],
+                off1 + x,
+                off2 + y,
+                limits,
+                scratch,
+                out,
+            );
+        }
+        None => {
+            out.push(delete);
+            out.push(insert);
+        }
+    }
+}
+
+/// Drop empty runs, merge adjacent runs of one kind, and order each change
+/// block as one deletion then one insertion. Within a block the deleted
+/// ranges are contiguous in `old` and the inserted ones in `new`, so merging
+/// is just widening.
+fn merge_ranges(ops: Vec<(Operation, Range<usize>)>) -> Vec<(Operation, Range<usize>)> {
+    let mut merged: Vec<(Operation, Range<usize>)> = vec![];
+    let mut delete: Option<Range<usize>> = None;
+    let mut insert: Option<Range<usize>> = None;
+    let widen = |slot: &mut Option<Range<usize>>, range: Range<usize>| match slot {
+        Some(r) => r.end = range.end,
+        None => *slot = Some(range),
+    };
+    for (op, range) in ops {
+        if range.is_empty() {
+            continue;
+        }
+        match op {
+            Operation::Delete => widen(&mut delete, range),
+            Operation::Insert => widen(&mut insert, range),
+            Operation::Equal => {
+                merged.extend(delete.take().map(|r| (Operation::Delete, r)));
+                merged.extend(insert.take().map(|r| (Operation::Insert, r)));
+                match merged.last_mut() {
+                    Some((Operation::Equal, r)) => r.end = range.end,
+                    _ => merged.push((op, range)),
+                }
+            }
+        }
+    }
+    merged.extend(delete.map(|r| (Operation::Delete, r)));
+    merged.extend(insert.map(|r| (Operation::Insert, r)));
+    merged
+}
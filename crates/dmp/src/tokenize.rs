@@ -10,7 +10,7 @@ use std::collections::HashMap;
 #[allow(clippy::ptr_arg)]
 impl Dmp {
     pub fn diff_words_tochars(
-        &mut self,
+        &self,
         text1: &String,
         text2: &String,
     ) -> (String, String, Vec<String>) {
@@ -31,13 +31,13 @@ impl Dmp {
         let mut wordarray: Vec<String> = vec!["".to_string()];
         let mut wordhash: HashMap<String, u32> = HashMap::new();
         let chars1 = self.diff_words_tochars_munge(text1, &mut wordarray, &mut wordhash);
-        let mut dmp = Dmp::new();
+        let dmp = Dmp::new();
         let chars2 = dmp.diff_words_tochars_munge(text2, &mut wordarray, &mut wordhash);
         (chars1, chars2, wordarray)
     }
 
     pub fn diff_words_tochars_munge(
-        &mut self,
+        &self,
         text: &String,
         wordarray: &mut Vec<String>,
         wordhash: &mut HashMap<String, u32>,
@@ -77,7 +77,7 @@ impl Dmp {
     }
 
     fn make_token_dict(
-        &mut self,
+        &self,
         word: &str,
         wordarray: &mut Vec<String>,
         wordhash: &mut HashMap<String, u32>,
@@ -90,7 +90,7 @@ impl Dmp {
     }
 
     pub fn diff_lines_tochars(
-        &mut self,
+        &self,
         text1: &Vec<char>,
         text2: &Vec<char>,
     ) -> (String, String, Vec<String>) {
@@ -113,7 +113,7 @@ impl Dmp {
     }
 
     pub fn diff_lines_tochars_munge(
-        &mut self,
+        &self,
         text: &Vec<char>,
         linearray: &mut Vec<String>,
         linehash: &mut HashMap<String, i32>,
@@ -138,7 +138,7 @@ impl Dmp {
     /// Args:
     /// diffs: Vector of diffs as changes.
     /// lineArray: Vector of unique strings.
-    pub fn diff_chars_tolines(&mut self, diffs: &mut Vec<Diff>, line_array: &Vec<String>) {
+    pub fn diff_chars_tolines(&self, diffs: &mut Vec<Diff>, line_array: &Vec<String>) {
         for diff in diffs.iter_mut() {
             let mut text: String = "".to_string();
             for ch in diff.text.chars() {
@@ -149,14 +149,15 @@ impl Dmp {
     }
 }
 
-/// FxHash-style hasher for the internal line-interning map: line packing
-/// hashes every line of both inputs, and SipHash's per-call overhead was
-/// ~13% of realistic diffs. NOT collision-hardened — acceptable here because
-/// the map only interns the lines of one diff call (an adversarial document
-/// degrades that document's own diff, nothing shared or persistent), and the
-/// public String-keyed API keeps std's SipHash.
+/// FxHash-style hasher for the internal line-interning map (and the patience
+/// anchor counts over its ids): line packing hashes every line of both
+/// inputs, and SipHash's per-call overhead was ~13% of realistic diffs. NOT
+/// collision-hardened — acceptable here because the map only interns the
+/// lines of one diff call (an adversarial document degrades that document's
+/// own diff, nothing shared or persistent), and the public String-keyed API
+/// keeps std's SipHash.
 #[derive(Default)]
-struct FxHasher(u64);
+pub(crate) struct FxHasher(u64);
 
 impl std::hash::Hasher for FxHasher {
     fn write(&mut self, bytes: &[u8]) {
@@ -177,11 +178,14 @@ impl std::hash::Hasher for FxHasher {
     }
 
     fn finish(&self) -> u64 {
-        self.0
+        // The multiply only carries entropy upward; rotate the well-mixed
+        // high bits down to where hash tables pick their bucket, or tokens
+        // differing only in their later bytes all collide.
+        self.0.rotate_left(26)
     }
 }
 
-type FxMap<K, V> = HashMap<K, V, std::hash::BuildHasherDefault<FxHasher>>;
+pub(crate) type FxMap<K, V> = HashMap<K, V, std::hash::BuildHasherDefault<FxHasher>>;
 
 fn fx_hash_bytes(bytes: &[u8]) -> u64 {
     use std::hash::Hasher;
@@ -220,6 +224,18 @@ impl LineArena {
             .collect()
     }
 
+    /// The text of the line behind a packed placeholder char.
+    pub(crate) fn line(&self, id: char) -> &str {
+        let (s, e) = self.spans[id_to_slot(id)];
+        &self.arena[s..e]
+    }
+
+    /// Bytes of text behind each placeholder char, for progress.
+    pub(crate) fn widths(&self) -> impl Fn(char) -> usize + 'static {
+        let widths: Vec<usize> = self.spans.iter().map(|&(s, e)| e - s).collect();
+        move |id| widths[id_to_slot(id)]
+    }
+
     /// Slot whose bytes equal the arena tip starting at `start`, if any.
     fn find_tip(&self, start: usize, h: u64) -> Option<usize> {
         let bucket = self.buckets.get(&h)?;
@@ -243,6 +259,16 @@ fn slot_to_id(slot: usize) -> u32 {
     }
 }
 
+/// The id slot behind a placeholder char: `slot_to_id` undone.
+fn id_to_slot(id: char) -> usize {
+    let id = id as usize;
+    if id >= 57344 {
+        id - 2048
+    } else {
+        id
+    }
+}
+
 /// True when `munge_arena` would pack `text` into a single line token: lines
 /// carry their trailing '\n', so that means the only '\n', if any, is the
 /// final char. Lets line mode skip the packing for one-line inputs, whose
@@ -324,9 +350,12 @@ fn munge_arena<T: DiffToken>(text: &[T], store: &mut LineArena) -> String {
     chars
 }
 
-/// Word-packing counterpart of `lines_tochars_arena`, for the opt-in word
-/// mode: words are maximal non-whitespace runs and every whitespace char is
-/// its own token — the public word munge's regex-\s semantics.
+/// Word-packing counterpart of `lines_tochars_arena`, for word mode and
+/// `diff_words`: words are maximal runs of non-whitespace grapheme clusters
+/// and every whitespace cluster is its own token. A cluster counts as
+/// whitespace when its first char does, so `\r\n` and a space carrying a
+/// combining mark stay whole and token boundaries are always cluster
+/// boundaries.
 pub(crate) fn words_tochars_arena<T: DiffToken>(
     text1: &[T],
     text2: &[T],
@@ -340,21 +369,25 @@ pub(crate) fn words_tochars_arena<T: DiffToken>(
 fn munge_words_arena<T: DiffToken>(text: &[T], store: &mut LineArena) -> String {
     let mut chars = "".to_string();
     let mut word_start: Option<usize> = None;
-    for i in 0..text.len() {
-        if text[i].is_word_sep() {
+    let mut exhausted = false;
+    T::walk_clusters(text, |from, to| {
+        if text[from].is_word_sep() {
             if let Some(start) = word_start.take() {
-                if !push_word_token(store, &mut chars, text, start, i) {
-                    return chars;
+                if !push_word_token(store, &mut chars, text, start, from) {
+                    exhausted = true;
+                    return false;
                 }
             }
-            if !push_word_token(store, &mut chars, text, i, i + 1) {
-                return chars;
+            if !push_word_token(store, &mut chars, text, from, to) {
+                exhausted = true;
+                return false;
             }
         } else if word_start.is_none() {
-            word_start = Some(i);
+            word_start = Some(from);
         }
-    }
-    if let Some(start) = word_start {
+        true
+    });
+    if let Some(start) = word_start.filter(|_| !exhausted) {
         push_word_token(store, &mut chars, text, start, text.len());
     }
     chars
@@ -372,6 +405,20 @@ fn push_word_token<T: DiffToken>(
 ) -> bool {
     let start = store.arena.len();
     T::append_to_arena(&text[from..to], &mut store.arena);
+    intern_tip(store, chars, start, |arena| {
+        T::append_to_arena(&text[from..], arena)
+    })
+}
+
+/// Intern the token written to the arena tip at `start` and append its
+/// placeholder char; `rest` rewrites the tip as the whole remaining text
+/// when the id space runs out. Returns false in that case.
+fn intern_tip(
+    store: &mut LineArena,
+    chars: &mut String,
+    start: usize,
+    rest: impl FnOnce(&mut String),
+) -> bool {
     let mut h = fx_hash_bytes(&store.arena.as_bytes()[start..]);
     if let Some(slot) = store.find_tip(start, h) {
         store.arena.truncate(start);
@@ -388,7 +435,7 @@ fn push_word_token<T: DiffToken>(
     // 1114111 is the biggest unicode scalar, so stop here
     if u32char == 1114111 {
         store.arena.truncate(start);
-        T::append_to_arena(&text[from..], &mut store.arena);
+        rest(&mut store.arena);
         h = fx_hash_bytes(&store.arena.as_bytes()[start..]);
         exhausted = true;
     }
@@ -398,22 +445,158 @@ fn push_word_token<T: DiffToken>(
     !exhausted
 }
 
+/// Splits text into the units a token diff ([`Dmp::diff_tokens`]) treats as
+/// atomic: every changed unit is deleted or inserted whole.
+///
+/// The tokens must be non-overlapping, in order, and concatenate back to
+/// the text exactly; empty tokens are ignored. Each distinct token is
+/// interned once, so a diff can hold up to about 1.1M distinct tokens (past
+/// that the remaining text becomes a single token).
+///
+/// ```
+/// use diff_match_patch::{Dmp, Operation, Tokenizer};
+///
+/// /// CSV cells, each with its trailing comma or newline.
+/// struct Cells;
+///
+/// impl Tokenizer for Cells {
+///     fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+///         text.split_inclusive([',', '\n']).collect()
+///     }
+/// }
+///
+/// let dmp = Dmp::new();
+/// let diffs = dmp.diff_tokens("a,bb,c\n", "a,bc,c\n", &Cells);
+/// assert_eq!(diffs[1].operation, Operation::Delete);
+/// assert_eq!(diffs[1].text, "bb,");
+/// ```
+pub trait Tokenizer {
+    /// Split `text` into tokens.
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str>;
+}
+
+/// Lines, each with its trailing `\n` (the line-mode vocabulary).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LineTokenizer;
+
+impl Tokenizer for LineTokenizer {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        text.split_inclusive('\n').collect()
+    }
+}
+
+/// Maximal runs of non-whitespace grapheme clusters, with each whitespace
+/// cluster a token of its own (the `diff_words` vocabulary). Keeps
+/// structured values such as `ABC-123`, `$24.99` and URLs whole.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WordTokenizer;
+
+impl Tokenizer for WordTokenizer {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        use unicode_segmentation::UnicodeSegmentation;
+        let mut tokens = vec![];
+        let mut word_start: Option<usize> = None;
+        for (i, cluster) in text.grapheme_indices(true) {
+            if cluster.starts_with(char::is_whitespace) {
+                if let Some(start) = word_start.take() {
+                    tokens.push(&text[start..i]);
+                }
+                tokens.push(cluster);
+            } else if word_start.is_none() {
+                word_start = Some(i);
+            }
+        }
+        if let Some(start) = word_start {
+            tokens.push(&text[start..]);
+        }
+        tokens
+    }
+}
+
+/// UAX #29 word boundaries: words, punctuation and whitespace runs split
+/// apart, with ideographic text segmented per character.
+#[cfg(feature = "grapheme")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UnicodeWordTokenizer;
+
+#[cfg(feature = "grapheme")]
+impl Tokenizer for UnicodeWordTokenizer {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        use unicode_segmentation::UnicodeSegmentation;
+        text.split_word_bounds().collect()
+    }
+}
+
+/// UAX #29 sentence boundaries; each sentence keeps its trailing
+/// whitespace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SentenceTokenizer;
+
+impl Tokenizer for SentenceTokenizer {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        use unicode_segmentation::UnicodeSegmentation;
+        text.split_sentence_bounds().collect()
+    }
+}
+
+/// Pack both texts into one placeholder char per unique token of
+/// `tokenizer`, into the same arena the line and word packers use.
+///
+/// Panics if the tokens do not concatenate back to their text.
+pub(crate) fn tokens_tochars_arena<K: Tokenizer + ?Sized>(
+    text1: &str,
+    text2: &str,
+    tokenizer: &K,
+) -> (String, String, LineArena) {
+    let mut store = LineArena::new();
+    let chars1 = munge_tokens_arena(text1, tokenizer, &mut store);
+    let chars2 = munge_tokens_arena(text2, tokenizer, &mut store);
+    (chars1, chars2, store)
+}
+
+fn munge_tokens_arena<K: Tokenizer + ?Sized>(
+    text: &str,
+    tokenizer: &K,
+    store: &mut LineArena,
+) -> String {
+    let mut chars = "".to_string();
+    let mut pos = 0;
+    for token in tokenizer.tokenize(text) {
+        assert!(
+            text[pos..].starts_with(token),
+            "tokens must concatenate back to the text"
+        );
+        if token.is_empty() {
+            continue;
+        }
+        let start = store.arena.len();
+        store.arena.push_str(token);
+        let from = pos;
+        if !intern_tip(store, &mut chars, start, |arena| {
+            arena.push_str(&text[from..])
+        }) {
+            return chars;
+        }
+        pos += token.len();
+    }
+    assert_eq!(pos, text.len(), "tokens must concatenate back to the text");
+    chars
+}
+
 /// Rehydrate line-packed diffs from the arena — the internal counterpart of
-/// `diff_chars_tolines`, indexing spans by raw placeholder value exactly as
-/// the public path indexes its linearray. Each placeholder token expands to
-/// its line's real chars in place, so the piece stays a `Vec<char>` run with
-/// no intermediate `String`.
+/// `diff_chars_tolines`, mapping each placeholder back to its slot past the
+/// surrogate gap. Each placeholder token expands to its line's real chars in
+/// place, so the piece stays a `Vec<char>` run with no intermediate `String`.
 pub(crate) fn chars_tolines_arena(diffs: &mut [TDiff], store: &LineArena) {
     for diff in diffs.iter_mut() {
         let mut total = 0;
         for &ch in &diff.data {
-            let (s, e) = store.spans[ch as usize];
+            let (s, e) = store.spans[id_to_slot(ch)];
             total += e - s;
         }
         let mut data: Vec<char> = Vec::with_capacity(total);
         for &ch in &diff.data {
-            let (s, e) = store.spans[ch as usize];
-            data.extend(store.arena[s..e].chars());
+            data.extend(store.line(ch).chars());
         }
         diff.data = data;
     }
@@ -550,6 +733,17 @@ impl GraphemePacker {
         text
     }
 
+    /// Bytes of text behind each packed char, for progress: the cluster's
+    /// for ids, the char's own otherwise.
+    pub fn widths(&self) -> impl Fn(char) -> usize + 'static {
+        let widths: HashMap<char, usize> = self
+            .reverse
+            .iter()
+            .map(|(&id, cluster)| (id, cluster.len()))
+            .collect();
+        move |ch| widths.get(&ch).copied().unwrap_or(ch.len_utf8())
+    }
+
     pub fn unpack_diffs(&self, diffs: &mut [Diff]) {
         for diff in diffs {
             diff.text = self.unpack(&diff.text);
@@ -596,4 +790,36 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn char_clusters_agree_with_graphemes() {
+        use unicode_segmentation::UnicodeSegmentation;
+        // Combining marks, CRLF, ZWJ emoji and flag pairs; the long ones
+        // cross the walk's window edges, and the last has a cluster wider
+        // than a window.
+        let short: &[&str] = &[
+            "",
+            "plain ascii",
+            "cafe\u{301} au lait",
+            "a\r\nb\r\r\n",
+            "\u{1F469}\u{200D}\u{1F4BB} at work",
+            "\u{1F1EB}\u{1F1F7}\u{1F1E9}\u{1F1EA}\u{1F1EE}",
+            "\u{1100}\u{1161}\u{11A8}한국어",
+            "x\u{200D}\u{0300}\u{0301}y",
+        ];
+        let mut cases: Vec<String> = short.iter().map(|t| t.to_string()).collect();
+        cases.push(short.concat().repeat(40));
+        cases.push("\u{1F1EB}".repeat(301));
+        cases.push(format!("a{}b", "\u{301}".repeat(700)));
+        for text in &cases {
+            let chars: Vec<char> = text.chars().collect();
+            let mut walked = vec![];
+            char::walk_clusters(&chars, |from, to| {
+                walked.push(chars[from..to].iter().collect::<String>());
+                true
+            });
+            let expected: Vec<&str> = text.graphemes(true).collect();
+            assert_eq!(expected, walked, "{text:?}");
+        }
+    }
 }
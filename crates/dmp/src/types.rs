@@ -1,6 +1,7 @@
 // Public data types (Dmp, Diff, Patch) and crate-internal helpers
 // shared by the diff, match and patch modules.
 
+use crate::apply::ApplyPolicy;
 use std::fmt;
 
 /// Which unit of text the diff engine treats as atomic.
@@ -23,9 +24,56 @@ pub enum Segmentation {
     Grapheme,
 }
 
+/// How `diff_main` aligns the two texts.
+// non_exhaustive so further algorithms can be added.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DiffAlgorithm {
+    /// Myers' bisect throughout (the historical behavior).
+    #[default]
+    Myers,
+    /// Patience diff: lines that occur exactly once in each text are matched
+    /// first and anchor the alignment; Myers fills in between the anchors.
+    /// Keeps moved functions and config sections whole instead of lining
+    /// up their braces and blank lines. Applies to the line-level pass, so
+    /// it needs `checklines` (and runs whatever the texts' size); changed
+    /// lines are then diffed char by char as usual.
+    Patience,
+    /// Histogram diff (git's `--histogram`): a patience variant that also
+    /// anchors on lines occurring a few times, matching the run of common
+    /// lines whose rarest line is least frequent. Copes better than patience
+    /// with files full of repeated lines. Same scope as `Patience`.
+    Histogram,
+}
+
+/// What counts as a word for `diff_words` and `Refine::Words` highlights.
+// non_exhaustive for the same reason as Segmentation.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WordVocabulary {
+    /// Maximal runs of non-whitespace, each whitespace cluster on its own
+    /// ([`crate::WordTokenizer`]). Keeps structured values such as
+    /// `ABC-123`, `$24.99` and URLs whole.
+    #[default]
+    Whitespace,
+    /// UAX #29 word boundaries ([`crate::UnicodeWordTokenizer`]):
+    /// punctuation splits off words (`dog.` diffs as `dog` and `.`) and
+    /// Chinese/Japanese text diffs per ideograph-word instead of per
+    /// whitespace-delimited run. Also splits structured values apart
+    /// (`$24.99` becomes `$` and `24.99`).
+    #[cfg(feature = "grapheme")]
+    Unicode,
+}
+
 pub struct Dmp {
     // Number of seconds to map a diff before giving up (None for infinity).
     pub diff_timeout: Option<f32>,
+    /*Bisect work allowed per diff call, in diagonals explored (None for
+    infinity). Unlike diff_timeout, the same inputs and budget always give the
+    same diff on any machine; set diff_timeout to None as well for fully
+    reproducible output. Once spent, the rest of the text is left as coarse
+    delete+insert blocks, as on a timeout.*/
+    pub diff_budget: Option<u64>,
     // Cost of an empty edit operation in terms of edit characters.
     pub edit_cost: i32,
     /*How far to search for a match (0 = exact location, 1000+ = broad match).
@@ -46,6 +94,8 @@ pub struct Dmp {
     1.0 = very loose).  Note that Match_Threshold controls how closely the
     end points of a delete need to match.*/
     pub patch_delete_threshold: f32,
+    // How closely patch_apply must find a patch's text before applying it.
+    pub patch_apply_policy: ApplyPolicy,
     // Unit of text the diff engine treats as atomic.
     pub segmentation: Segmentation,
     /*Opt-in word-mode speedup: large edit blocks are diffed over packed word
@@ -56,11 +106,64 @@ pub struct Dmp {
     boundaries snap to word boundaries first, so it is NOT byte-identical to
     the reference implementation's output — hence off by default.*/
     pub word_mode: bool,
+    // Word boundaries used by diff_words and word-refined line diffs.
+    pub word_vocabulary: WordVocabulary,
+    // Alignment algorithm of diff_main's line-level pass and diff_lines.
+    pub diff_algorithm: DiffAlgorithm,
+}
+
+/// The kind of edit a [`Diff`] chunk records. The discriminants are the
+/// historical wire codes (-1 delete, 0 equal, 1 insert), so `as i32` and the
+/// `From` conversions below round-trip with code that still speaks integers.
+#[repr(i32)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Operation {
+    Delete = -1,
+    Equal = 0,
+    Insert = 1,
+}
+
+impl Operation {
+    /// The operation with wire code `code`, or `None` for anything but -1, 0
+    /// and 1. Use this rather than `From` on codes read from outside (a
+    /// corpus, a database): `From` accepts every integer. (A `TryFrom<i32>`
+    /// cannot sit beside `From<i32>`, which already implies an infallible
+    /// one.)
+    pub fn from_code(code: i32) -> Option<Operation> {
+        match code {
+            -1 => Some(Operation::Delete),
+            0 => Some(Operation::Equal),
+            1 => Some(Operation::Insert),
+            _ => None,
+        }
+    }
+}
+
+impl From<i32> for Operation {
+    /// Lossy: maps an integer code by sign, so a typo like `2` silently
+    /// becomes `Insert`. Negative deletes, zero is an equality, positive
+    /// inserts (any positive code was an insert to the historical
+    /// `match op { 0 => .., -1 => .., _ => .. }` consumers). Kept so
+    /// pre-enum code builds while it migrates; see `Operation::from_code`
+    /// for a checked conversion.
+    fn from(code: i32) -> Operation {
+        match code.signum() {
+            -1 => Operation::Delete,
+            0 => Operation::Equal,
+            _ => Operation::Insert,
+        }
+    }
+}
+
+impl From<Operation> for i32 {
+    fn from(op: Operation) -> i32 {
+        op as i32
+    }
 }
 
 pub struct Diff {
     // diff object
-    pub operation: i32,
+    pub operation: Operation,
     pub text: String,
 }
 pub struct Patch {
@@ -72,9 +175,59 @@ pub struct Patch {
     pub length2: i32,
 }
 impl Diff {
-    // A new diff diff object created.
-    pub fn new(operation: i32, text: String) -> Diff {
-        Diff { operation, text }
+    // A new diff diff object created. Integer codes are still accepted
+    // (via `From<i32>`) so callers can migrate to `Operation` gradually.
+    pub fn new(operation: impl Into<Operation>, text: String) -> Diff {
+        Diff {
+            operation: operation.into(),
+            text,
+        }
+    }
+}
+
+/// A diff chunk borrowing its text from the diffed inputs (text1 for
+/// equalities and deletions, text2 for insertions); see `Dmp::diff_main_ref`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DiffRef<'a> {
+    pub operation: Operation,
+    pub text: &'a str,
+}
+
+impl<'a> DiffRef<'a> {
+    pub fn new(operation: Operation, text: &'a str) -> DiffRef<'a> {
+        DiffRef { operation, text }
+    }
+}
+
+impl From<DiffRef<'_>> for Diff {
+    fn from(diff: DiffRef<'_>) -> Diff {
+        Diff::new(diff.operation, diff.text.to_string())
+    }
+}
+
+/// Read access shared by [`Diff`] and [`DiffRef`], so the diff-derived
+/// helpers (`diff_text1`, `diff_levenshtein`, `diff_todelta`, ...) accept
+/// either form.
+pub trait DiffChunk {
+    fn operation(&self) -> Operation;
+    fn text(&self) -> &str;
+}
+
+impl DiffChunk for Diff {
+    fn operation(&self) -> Operation {
+        self.operation
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl DiffChunk for DiffRef<'_> {
+    fn operation(&self) -> Operation {
+        self.operation
+    }
+    fn text(&self) -> &str {
+        self.text
     }
 }
 
@@ -147,7 +300,9 @@ pub(crate) fn find_char(cha: char, text: &[char], start: usize) -> i32 {
 /// on bytes instead — a bijection there, so every comparison, index, and
 /// length matches and the output is byte-identical, while the text is used
 /// zero-copy (no `Vec<char>` materialization, no UTF-8 encode/decode in the
-/// line arena, and a quarter of the memory traffic in bisect).
+/// line arena, and a quarter of the memory traffic in bisect). `diff_bytes`
+/// runs the byte recursion on arbitrary bytes, each carried as the char of
+/// the same value (U+0000–U+00FF) once widened.
 pub(crate) trait DiffToken: Copy + Eq {
     const NEWLINE: Self;
     /// Widen a token run to `char`s: the diff pieces are carried as
@@ -155,12 +310,21 @@ pub(crate) trait DiffToken: Copy + Eq {
     /// encoded to UTF-8 exactly once, at the final `materialize`. On the u8
     /// (ASCII) recursion this is a byte→char widen; on char it is a copy.
     fn to_tokens(tokens: &[Self]) -> Vec<char>;
+    /// Append `text` as tokens. Only called on the u8 recursion with ASCII
+    /// text (patch_apply's fast path), where that is its bytes.
+    fn extend_from_str(tokens: &mut Vec<Self>, text: &str);
     /// Append a token run to the line arena (always UTF-8).
     fn append_to_arena(tokens: &[Self], arena: &mut String);
     /// Word-mode separator, matching `char::is_whitespace` (which on ASCII
     /// is exactly {\t, \n, VT, FF, \r, space} — note `u8::is_ascii_whitespace`
     /// omits VT, so the byte impl cannot delegate to it).
     fn is_word_sep(self) -> bool;
+    /// Call `f(from, to)` for each extended grapheme cluster of `tokens`, in
+    /// order, until it returns false. On u8 the only multi-token cluster is
+    /// `\r\n` (the ASCII fast path never sees any other).
+    fn walk_clusters(tokens: &[Self], f: impl FnMut(usize, usize) -> bool);
+    /// The token as the char it widens to in `to_tokens`.
+    fn as_char(self) -> char;
 }
 
 impl DiffToken for char {
@@ -168,12 +332,50 @@ impl DiffToken for char {
     fn to_tokens(tokens: &[char]) -> Vec<char> {
         tokens.to_vec()
     }
+    fn extend_from_str(tokens: &mut Vec<char>, text: &str) {
+        tokens.extend(text.chars());
+    }
     fn append_to_arena(tokens: &[char], arena: &mut String) {
         arena.extend(tokens.iter());
     }
     fn is_word_sep(self) -> bool {
         self.is_whitespace()
     }
+    fn as_char(self) -> char {
+        self
+    }
+    fn walk_clusters(tokens: &[char], mut f: impl FnMut(usize, usize) -> bool) {
+        use unicode_segmentation::UnicodeSegmentation;
+        // Segment a window at a time through one reused buffer rather than
+        // copying the whole slice. Each window starts on a cluster boundary,
+        // so it segments as the whole text would, except that its last
+        // cluster may run on past it; that one is left for the next window.
+        let mut window = String::new();
+        let mut width = 256;
+        let mut from = 0;
+        while from < tokens.len() {
+            let end = usize::min(from + width, tokens.len());
+            window.clear();
+            window.extend(&tokens[from..end]);
+            let mut clusters = window.graphemes(true).peekable();
+            let mut walked = false;
+            while let Some(cluster) = clusters.next() {
+                if clusters.peek().is_none() && end < tokens.len() {
+                    break;
+                }
+                let to = from + cluster.chars().count();
+                if !f(from, to) {
+                    return;
+                }
+                from = to;
+                walked = true;
+            }
+            if !walked {
+                // One cluster fills the window.
+                width *= 2;
+            }
+        }
+    }
 }
 
 impl DiffToken for u8 {
@@ -181,12 +383,39 @@ impl DiffToken for u8 {
     fn to_tokens(tokens: &[u8]) -> Vec<char> {
         tokens.iter().map(|&b| b as char).collect()
     }
+    fn extend_from_str(tokens: &mut Vec<u8>, text: &str) {
+        debug_assert!(text.is_ascii());
+        tokens.extend_from_slice(text.as_bytes());
+    }
     fn append_to_arena(tokens: &[u8], arena: &mut String) {
-        arena.push_str(std::str::from_utf8(tokens).expect("ascii fast path"));
+        // Bytes above ASCII only come from `diff_bytes`; they widen to the
+        // char of the same value, as in `to_tokens`.
+        if tokens.is_ascii() {
+            arena.push_str(std::str::from_utf8(tokens).expect("ascii fast path"));
+        } else {
+            arena.extend(tokens.iter().map(|&b| b as char));
+        }
     }
     fn is_word_sep(self) -> bool {
         matches!(self, b'\t' | b'\n' | 0x0B | 0x0C | b'\r' | b' ')
     }
+    fn as_char(self) -> char {
+        self as char
+    }
+    fn walk_clusters(tokens: &[u8], mut f: impl FnMut(usize, usize) -> bool) {
+        let mut from = 0;
+        while from < tokens.len() {
+            let to = if tokens[from..].starts_with(b"\r\n") {
+                from + 2
+            } else {
+                from + 1
+            };
+            if !f(from, to) {
+                return;
+            }
+            from = to;
+        }
+    }
 }
 
 /// Internal token-carrying diff piece. The recursion and every cleanup pass
@@ -194,12 +423,12 @@ impl DiffToken for u8 {
 /// UTF-8 encode/decode round-trips that dominated the char-path profiles are
 /// gone; the public `Diff` (owned `String`) is built once, at `materialize`.
 pub(crate) struct TDiff {
-    pub operation: i32,
+    pub operation: Operation,
     pub data: Vec<char>,
 }
 
 impl TDiff {
-    pub(crate) fn new(operation: i32, data: Vec<char>) -> TDiff {
+    pub(crate) fn new(operation: Operation, data: Vec<char>) -> TDiff {
         TDiff { operation, data }
     }
 
@@ -212,7 +441,7 @@ impl TDiff {
 
 impl fmt::Debug for Diff {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "\n  {{ {}: {} }}", self.operation, self.text)
+        write!(f, "\n  {{ {}: {} }}", self.operation as i32, self.text)
     }
 }
 
@@ -247,6 +476,15 @@ impl Clone for Patch {
     }
 }
 
+// Callers share one Dmp (and its diffs and patches) across threads; keep
+// every field plain data so these stay Send + Sync.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Dmp>();
+    assert_send_sync::<Diff>();
+    assert_send_sync::<Patch>();
+};
+
 impl Default for Dmp {
     fn default() -> Self {
         Self::new()
@@ -258,7 +496,9 @@ impl Dmp {
         // it will give a new dmp object.
         Dmp {
             diff_timeout: None,
+            diff_budget: None,
             patch_delete_threshold: 0.5,
+            patch_apply_policy: ApplyPolicy::default(),
             edit_cost: 0,
             match_distance: 1000,
             patch_margin: 4,
@@ -266,6 +506,8 @@ impl Dmp {
             match_threshold: 0.5,
             segmentation: Segmentation::default(),
             word_mode: false,
+            word_vocabulary: WordVocabulary::default(),
+            diff_algorithm: DiffAlgorithm::default(),
         }
     }
 }
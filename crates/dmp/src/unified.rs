@@ -0,0 +1,391 @@
+// GNU unified diff support (`diff -u`). Output: the hunks of `diff_lines` in
+// the format `patch(1)`, `git apply` and review tools read. Input: a
+// parser for (multi-file) unified diffs and an applier that locates each hunk
+// with the fuzzy `patch_apply` machinery instead of strict line matching.
+
+use crate::apply::PatchReport;
+use crate::error::ParseError;
+use crate::lines::{LineOptions, Refine};
+use crate::types::{Diff, Dmp, Operation, Patch};
+
+/// Layout choices for [`Dmp::diff_unified`]. The default matches `diff -u`
+/// with `a`/`b` labels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnifiedOptions {
+    /// Unchanged lines kept around each change. Changes separated by at most
+    /// twice this many unchanged lines share a hunk.
+    pub context: usize,
+    /// Label on the `---` line (a path, optionally followed by a tab and a
+    /// timestamp, as `diff -u` writes it).
+    pub old_label: String,
+    /// Label on the `+++` line.
+    pub new_label: String,
+    /// Follow a final line that lacks its newline with
+    /// `\ No newline at end of file`. Without the marker such a line is
+    /// printed with a newline, so the output no longer records the
+    /// difference.
+    pub no_newline_marker: bool,
+}
+
+impl Default for UnifiedOptions {
+    fn default() -> Self {
+        UnifiedOptions {
+            context: 3,
+            old_label: "a".to_string(),
+            new_label: "b".to_string(),
+            no_newline_marker: true,
+        }
+    }
+}
+
+impl Dmp {
+    /// Diff two texts line by line and format the result as a GNU unified
+    /// diff. Every changed line is listed whole; there is no char-level
+    /// refinement. Identical texts give an empty string, as `diff` prints
+    /// nothing for them.
+    ///
+    /// Args:
+    /// text1: Old text.
+    /// text2: New text.
+    /// options: Context size, file labels and newline marker.
+    ///
+    /// Returns:
+    /// Unified diff text.
+    pub fn diff_unified(&self, text1: &str, text2: &str, options: &UnifiedOptions) -> String {
+        let line_options = LineOptions {
+            context: options.context,
+            refine: Refine::None,
+        };
+        let hunks = self.diff_lines(text1, text2, &line_options);
+        if hunks.is_empty() {
+            return String::new();
+        }
+        let mut out = format!("--- {}\n+++ {}\n", options.old_label, options.new_label);
+        for hunk in hunks {
+            out.push_str("@@ -");
+            push_range(&mut out, hunk.old_start, hunk.old_len);
+            out.push_str(" +");
+            push_range(&mut out, hunk.new_start, hunk.new_len);
+            out.push_str(" @@\n");
+            for line in &hunk.lines {
+                out.push(match line.operation {
+                    Operation::Delete => '-',
+                    Operation::Insert => '+',
+                    Operation::Equal => ' ',
+                });
+                out.push_str(&line.text);
+                if !line.text.ends_with('\n') {
+                    out.push('\n');
+                    if options.no_newline_marker {
+                        out.push_str("\\ No newline at end of file\n");
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// One file's section of a parsed unified diff.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnifiedFile {
+    /// Text after `--- `, verbatim (path plus any tab-separated timestamp).
+    pub old_label: String,
+    /// Text after `+++ `, verbatim.
+    pub new_label: String,
+    pub hunks: Vec<UnifiedHunk>,
+}
+
+/// One `@@` hunk. Line positions are zero-based counts of the lines before
+/// the hunk, so `@@ -3,2 +3,0 @@` has `old_start` 2 and `new_start` 3 (an
+/// empty range is anchored after the line it names).
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnifiedHunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    /// Context, deleted and inserted lines with their newlines, consecutive
+    /// lines of one kind merged into a single `Diff`.
+    pub diffs: Vec<Diff>,
+}
+
+impl Dmp {
+    /// Parse a unified diff, possibly covering several files. Lines outside
+    /// the `---`/`+++` headers and their hunks (`diff --git`, `index`, mail
+    /// headers, commentary) are skipped. A blank line inside a hunk counts
+    /// as an empty context line, as `patch(1)` reads it.
+    ///
+    /// Args:
+    /// text: Unified diff text.
+    ///
+    /// Returns:
+    /// The files in order, or the first parse error.
+    pub fn unified_from_text(&self, text: &str) -> Result<Vec<UnifiedFile>, ParseError> {
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        let lines: Vec<&str> = text.split('\n').collect();
+        let mut files: Vec<UnifiedFile> = vec![];
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i];
+            if let Some(old_label) = line.strip_prefix("--- ") {
+                if let Some(new_label) = lines.get(i + 1).and_then(|l| l.strip_prefix("+++ ")) {
+                    files.push(UnifiedFile {
+                        old_label: old_label.to_string(),
+                        new_label: new_label.to_string(),
+                        hunks: vec![],
+                    });
+                    i += 2;
+                    continue;
+                }
+            }
+            if line.starts_with("@@ ") {
+                let file = files.last_mut().ok_or_else(|| ParseError::BadHeader {
+                    index: i,
+                    text: line.to_string(),
+                })?;
+                let (hunk, next) = parse_hunk(&lines, i)?;
+                file.hunks.push(hunk);
+                i = next;
+                continue;
+            }
+            i += 1;
+        }
+        Ok(files)
+    }
+
+    /// Apply one file's hunks to `text`. Each hunk becomes a `Patch` at the
+    /// char offset of its line in the text (shifted by where the hunks
+    /// before it landed and the lines they added) and goes through
+    /// `patch_apply`, so it is found by the same Bitap search,
+    /// `match_threshold`, `match_distance` and `patch_delete_threshold` rules
+    /// rather than by exact line numbers. A hunk applies whole or not at
+    /// all.
+    ///
+    /// Args:
+    /// file: Parsed unified diff section.
+    /// text: Old text.
+    ///
+    /// Returns:
+    /// The new text, and whether each hunk applied.
+    pub fn patch_apply_unified(&self, file: &UnifiedFile, text: &str) -> (String, Vec<bool>) {
+        let mut text = text.to_string();
+        let mut results = Vec::with_capacity(file.hunks.len());
+        // Net lines added by the hunks applied so far.
+        let mut line_delta: isize = 0;
+        for hunk in &file.hunks {
+            let chars: Vec<char> = text.chars().collect();
+            let line = (hunk.old_start as isize + line_delta).max(0) as usize;
+            let guess = line_offset(&chars, line);
+            let old_text = crate::patch::diff_text1_tokens::<char>(&hunk.diffs);
+            let mut diffs = hunk.diffs.clone();
+            // patch_apply's padding takes a context shorter than
+            // patch_margin to mean the patch sits at the very start or end of
+            // the text. Top such sides (blank lines, `diff -U0`) up with the
+            // text around the hunk, as patch(1) places unanchored hunks by
+            // line number alone; but only around a verbatim copy of the
+            // hunk's old lines, nearest the line number, since context copied
+            // from the text always matches and would hide a wrong spot.
+            let margin = self.patch_margin.max(0) as usize;
+            let short = context_len(diffs.first()) < margin || context_len(diffs.last()) < margin;
+            let mut start = guess;
+            if short && chars.get(guess..guess + old_text.len()) != Some(&old_text[..]) {
+                let (found, _) =
+                    crate::match_::match_exact(self, &chars, &old_text, guess as i32, chars.len());
+                if found < 0 {
+                    results.push(false);
+                    continue;
+                }
+                start = found as usize;
+            }
+            let at = start;
+            let end = usize::min(start + old_text.len(), chars.len());
+            let lead = context_len(diffs.first());
+            let take = usize::min(margin.saturating_sub(lead), start);
+            let start = start - take;
+            if take > 0 {
+                let mut prefix: String = chars[start..start + take].iter().collect();
+                if lead > 0 {
+                    prefix.push_str(&diffs[0].text);
+                    diffs[0].text = prefix;
+                } else {
+                    diffs.insert(0, Diff::new(Operation::Equal, prefix));
+                }
+            }
+            let trail = context_len(diffs.last());
+            let take = usize::min(margin.saturating_sub(trail), chars.len() - end);
+            if take > 0 {
+                let suffix: String = chars[end..end + take].iter().collect();
+                match diffs.last_mut() {
+                    Some(last) if trail > 0 => last.text.push_str(&suffix),
+                    _ => diffs.push(Diff::new(Operation::Equal, suffix)),
+                }
+            }
+            let mut length1 = 0;
+            let mut length2 = 0;
+            for diff in &diffs {
+                let len = diff.text.chars().count() as i32;
+                if diff.operation != Operation::Insert {
+                    length1 += len;
+                }
+                if diff.operation != Operation::Delete {
+                    length2 += len;
+                }
+            }
+            let patches = vec![Patch::new(
+                diffs,
+                start as i32,
+                start as i32,
+                length1,
+                length2,
+            )];
+            // All or nothing: a hunk longer than match_maxbits is applied in
+            // pieces, and keeping some of them would half-apply it.
+            let (patched, reports) = self.patch_apply_report(&patches, &text);
+            let ok = reports.iter().all(PatchReport::applied);
+            if ok {
+                // Follow the hunk to where it landed: the lines the text had
+                // drifted by there, plus the lines it really added.
+                let drift = reports.first().and_then(|r| r.delta).unwrap_or(0);
+                let landed = (at as isize + drift as isize).clamp(0, chars.len() as isize);
+                let (from, to) = if landed < guess as isize {
+                    (landed as usize, guess)
+                } else {
+                    (guess, landed as usize)
+                };
+                let crossed = chars[from..to].iter().filter(|&&c| c == '\n').count() as isize;
+                line_delta += if landed < guess as isize {
+                    -crossed
+                } else {
+                    crossed
+                };
+                line_delta += line_count(&patched) - line_count(&text);
+                text = patched;
+            }
+            results.push(ok);
+        }
+        (text, results)
+    }
+}
+
+/// Char offset where zero-based line `line` starts, or the text length past
+/// the last line.
+fn line_offset(chars: &[char], line: usize) -> usize {
+    if line == 0 {
+        return 0;
+    }
+    chars
+        .iter()
+        .enumerate()
+        .filter(|&(_, &c)| c == '\n')
+        .nth(line - 1)
+        .map_or(chars.len(), |(i, _)| i + 1)
+}
+
+/// Newlines in `text`.
+fn line_count(text: &str) -> isize {
+    text.bytes().filter(|&b| b == b'\n').count() as isize
+}
+
+/// Chars of context a hunk edge carries: the edge diff's length when it is
+/// an equality.
+fn context_len(diff: Option<&Diff>) -> usize {
+    match diff {
+        Some(diff) if diff.operation == Operation::Equal => diff.text.chars().count(),
+        _ => 0,
+    }
+}
+
+/// Parse the hunk whose header is `lines[index]`; returns it with the index
+/// of the first line after its body.
+fn parse_hunk(lines: &[&str], index: usize) -> Result<(UnifiedHunk, usize), ParseError> {
+    let header = lines[index];
+    let bad_header = || ParseError::BadHeader {
+        index,
+        text: header.to_string(),
+    };
+    let bad_length = || ParseError::HunkLength {
+        index,
+        text: header.to_string(),
+    };
+    // Anything after the closing `@@` is a section heading (`diff -p`).
+    let (coords, _) = header
+        .strip_prefix("@@ -")
+        .and_then(|r| r.split_once(" @@"))
+        .ok_or_else(bad_header)?;
+    let (c1, c2) = coords.split_once(" +").ok_or_else(bad_header)?;
+    let (old_start, old_len) = crate::patch::parse_coords(index, c1)?;
+    let (new_start, new_len) = crate::patch::parse_coords(index, c2)?;
+    let mut hunk = UnifiedHunk {
+        old_start: old_start.max(0) as usize,
+        old_len: old_len as usize,
+        new_start: new_start.max(0) as usize,
+        new_len: new_len as usize,
+        diffs: vec![],
+    };
+
+    let mut old_left = hunk.old_len;
+    let mut new_left = hunk.new_len;
+    let mut i = index + 1;
+    loop {
+        let line = match lines.get(i) {
+            Some(line) => *line,
+            None if old_left == 0 && new_left == 0 => break,
+            None => return Err(bad_length()),
+        };
+        let (sign, body) = match line.chars().next() {
+            Some(sign) => (sign, &line[sign.len_utf8()..]),
+            None => (' ', ""),
+        };
+        let op = match sign {
+            '\\' => {
+                // "\ No newline at end of file": the previous line had none.
+                if let Some(last) = hunk.diffs.last_mut() {
+                    if last.text.ends_with('\n') {
+                        last.text.pop();
+                    }
+                }
+                i += 1;
+                continue;
+            }
+            _ if old_left == 0 && new_left == 0 => break,
+            ' ' if old_left > 0 && new_left > 0 => {
+                old_left -= 1;
+                new_left -= 1;
+                Operation::Equal
+            }
+            '-' if old_left > 0 => {
+                old_left -= 1;
+                Operation::Delete
+            }
+            '+' if new_left > 0 => {
+                new_left -= 1;
+                Operation::Insert
+            }
+            ' ' | '-' | '+' => return Err(bad_length()),
+            _ => {
+                return Err(ParseError::InvalidMode {
+                    index: i,
+                    text: line.to_string(),
+                })
+            }
+        };
+        match hunk.diffs.last_mut() {
+            Some(last) if last.operation == op => last.text.push_str(body),
+            _ => hunk.diffs.push(Diff::new(op, body.to_string())),
+        }
+        hunk.diffs.last_mut().unwrap().text.push('\n');
+        i += 1;
+    }
+    Ok((hunk, i))
+}
+
+/// Append a hunk range: `start,len` with `start` one-based, `,1` omitted,
+/// and an empty range anchored on the line before it (`diff -u`'s rules).
+fn push_range(out: &mut String, before: usize, len: usize) {
+    match len {
+        0 => out.push_str(&format!("{},0", before)),
+        1 => out.push_str(&(before + 1).to_string()),
+        _ => out.push_str(&format!("{},{}", before + 1, len)),
+    }
+}
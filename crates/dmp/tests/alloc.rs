@@ -0,0 +1,102 @@
+//! Allocation accounting for `diff_main_ref`, through a counting global
+//! allocator. Counts are per thread, so the tests can run in parallel.
+
+use diff_match_patch::{DiffRef, Dmp};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct Counting;
+
+// Signed: a thread may free memory another thread allocated.
+thread_local! {
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+fn track(grow: usize, shrink: usize) {
+    // Allocations during thread teardown find the counters gone; skip them.
+    let _ = LIVE.try_with(|live| {
+        let now = live.get() + grow as isize - shrink as isize;
+        live.set(now);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(now)));
+    });
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        track(layout.size(), 0);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        track(0, layout.size());
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        track(new_size, layout.size());
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Run `f`, returning its result, the peak bytes allocated on top of what
+/// was live before, and the bytes still held once it returns.
+fn measure<R>(f: impl FnOnce() -> R) -> (R, usize, usize) {
+    let base = LIVE.with(Cell::get);
+    PEAK.with(|peak| peak.set(base));
+    let result = f();
+    let peak = PEAK.with(Cell::get) - base;
+    let held = LIVE.with(Cell::get) - base;
+    (result, peak as usize, held as usize)
+}
+
+fn texts() -> (String, String) {
+    let mut old = String::new();
+    let mut new = String::new();
+    for i in 0..400 {
+        old += &format!("line {i}: the quick brown fox — über alles\n");
+        new += &format!(
+            "line {i}: the quick {} fox — über alles\n",
+            if i % 9 == 0 { "red" } else { "brown" }
+        );
+    }
+    (old, new)
+}
+
+#[test]
+fn diff_main_ref_keeps_no_text() {
+    let (old, new) = texts();
+    let dmp = Dmp::new();
+    let (diffs, _, held) = measure(|| dmp.diff_main_ref(&old, &new, true));
+    assert!(diffs.len() > 100);
+    // Only the chunk list itself outlives the call.
+    assert_eq!(diffs.capacity() * std::mem::size_of::<DiffRef>(), held);
+    let (owned, _, held) = measure(|| dmp.diff_main(&old, &new, true));
+    assert!(held > owned.capacity() * std::mem::size_of::<DiffRef>());
+}
+
+#[test]
+fn diff_main_ref_peaks_no_higher_than_diff_main() {
+    // The engine works on owned copies either way; borrowing only saves
+    // the result's text.
+    let (old, new) = texts();
+    let dmp = Dmp::new();
+    let (_, peak_ref, _) = measure(|| dmp.diff_main_ref(&old, &new, true));
+    let (_, peak_owned, _) = measure(|| dmp.diff_main(&old, &new, true));
+    assert!(peak_ref <= peak_owned, "{peak_ref} > {peak_owned}");
+    assert!(peak_ref > old.len(), "the engine copies the text");
+}
+
+#[cfg(feature = "grapheme")]
+#[test]
+fn diff_main_ref_keeps_no_text_in_grapheme_mode() {
+    use diff_match_patch::Segmentation;
+    let (old, new) = texts();
+    let mut dmp = Dmp::new();
+    dmp.segmentation = Segmentation::Grapheme;
+    let (diffs, _, held) = measure(|| dmp.diff_main_ref(&old, &new, true));
+    assert_eq!(diffs.capacity() * std::mem::size_of::<DiffRef>(), held);
+}
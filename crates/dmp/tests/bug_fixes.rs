@@ -2,7 +2,7 @@
 //! plan's intentional-change ledger). These assert the CORRECT behavior and
 //! must stay green permanently.
 
-use diff_match_patch::{Diff, Dmp};
+use diff_match_patch::{Diff, Dmp, Operation};
 
 /// Two large multi-line texts sharing big common blocks interleaved with small
 /// distinct lines. Both sides exceed 100 chars so `checklines = true` takes the
@@ -26,7 +26,10 @@ fn interleaved_blocks() -> (String, String) {
 }
 
 fn equality_count(diffs: &[Diff]) -> usize {
-    diffs.iter().filter(|d| d.operation == 0).count()
+    diffs
+        .iter()
+        .filter(|d| d.operation == Operation::Equal)
+        .count()
 }
 
 /// Rebuild (text1, text2) from a diff: equalities+deletions form text1,
@@ -35,10 +38,10 @@ fn rebuild(diffs: &[Diff]) -> (String, String) {
     let mut t1 = String::new();
     let mut t2 = String::new();
     for d in diffs {
-        if d.operation != 1 {
+        if d.operation != Operation::Insert {
             t1 += &d.text;
         }
-        if d.operation != -1 {
+        if d.operation != Operation::Delete {
             t2 += &d.text;
         }
     }
@@ -87,7 +90,7 @@ fn bug1_line_mode_respects_diff_timeout() {
 /// inside `patch_apply` instead of applying as a no-op.
 #[test]
 fn bug2_patch_apply_handles_header_only_patch() {
-    let mut dmp = Dmp::new();
+    let dmp = Dmp::new();
     let mut patches = dmp.patch_from_text("@@ -1,0 +1,0 @@\n".to_string());
     assert_eq!(patches.len(), 1);
     let (patched, results) = dmp.patch_apply(&mut patches, "hello world");
@@ -103,7 +106,7 @@ fn bug2_patch_apply_handles_header_only_patch() {
 /// header-only. Expected output verified identical to the JS oracle.
 #[test]
 fn bug2_patch_apply_handles_trailing_header_only_hunk() {
-    let mut dmp = Dmp::new();
+    let dmp = Dmp::new();
     let mut patches =
         dmp.patch_from_text("@@ -1,8 +1,9 @@\n abcd\n+X\n efgh\n@@ -20,0 +20,0 @@\n".to_string());
     assert_eq!(patches.len(), 2);
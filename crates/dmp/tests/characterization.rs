@@ -11,7 +11,7 @@ use diff_match_patch::{Diff, Dmp};
 /// The pre-rewrite encoder left it bare — fixed in the Stage-4 delta rewrite.
 #[test]
 fn delta_percent_escaped() {
-    let mut d = Dmp::new();
+    let d = Dmp::new();
     let mut diffs = vec![Diff::new(
         1,
         "tag <x> \"q\" {y} 100% back\\slash".to_string(),
@@ -28,7 +28,7 @@ fn delta_percent_escaped() {
 /// 1) — fixed in the Stage-4 patch rewrite.
 #[test]
 fn zero_length_hunk_roundtrips() {
-    let mut d = Dmp::new();
+    let d = Dmp::new();
     let input = "@@ -5,0 +5,0 @@\n".to_string();
     let mut patches = d.patch_from_text(input.clone());
     assert_eq!(d.patch_to_text(&mut patches), input);
@@ -46,7 +46,7 @@ fn zero_length_hunk_roundtrips() {
 fn patch_make_absorbs_margin_sized_equality() {
     let t1 = "alpha line one\nbeta line two\ngamma line three\ndelta line four\nepsilon line five\nzeta line six\neta line seven\ntheta line eight\n";
     let t2 = "alpha line one\nbeta line 2 changed\ngamma line three\ndelta line four\nnew line inserted here\nepsilon line five\nzeta line six\ntheta line eight\niota line nine\n";
-    let mut d = Dmp::new();
+    let d = Dmp::new();
     let mut patches = d.patch_make1(t1, t2);
     assert_eq!(patches.len(), 4);
     assert_eq!(
@@ -7,7 +7,7 @@
 //!
 //! Known deviations from the oracle are pinned in tests/characterization.rs, not here.
 
-use diff_match_patch::{Diff, Dmp};
+use diff_match_patch::{Diff, Dmp, Operation};
 use serde::Deserialize;
 
 /// Keep in sync with CASES in oracle/generate.mjs; an empty or truncated corpus must
@@ -30,6 +30,8 @@ struct Case {
     #[serde(rename = "diffSemantic")]
     diff_semantic: Vec<(i32, String)>,
     delta: String,
+    #[serde(rename = "prettyHtml")]
+    pretty_html: String,
     #[serde(rename = "patchText")]
     patch_text: String,
     #[serde(rename = "patchApplied")]
@@ -51,13 +53,16 @@ fn corpus() -> Vec<Case> {
 fn to_tuples(diffs: &[Diff]) -> Vec<(i32, String)> {
     diffs
         .iter()
-        .map(|d| (d.operation, d.text.clone()))
+        .map(|d| (d.operation.into(), d.text.clone()))
         .collect()
 }
 
 fn from_tuples(rows: &[(i32, String)]) -> Vec<Diff> {
     rows.iter()
-        .map(|(op, t)| Diff::new(*op, t.clone()))
+        .map(|(op, t)| {
+            let op = Operation::from_code(*op).unwrap_or_else(|| panic!("bad op code {}", op));
+            Diff::new(op, t.clone())
+        })
         .collect()
 }
 
@@ -109,10 +114,23 @@ fn golden_delta_decode() {
     }
 }
 
+#[test]
+fn golden_pretty_html() {
+    for c in corpus() {
+        let diffs = from_tuples(&c.diff);
+        assert_eq!(
+            Dmp::new().diff_pretty_html(&diffs),
+            c.pretty_html,
+            "pretty html mismatch in case '{}'",
+            c.name
+        );
+    }
+}
+
 #[test]
 fn golden_patch_make() {
     for c in corpus() {
-        let mut d = Dmp::new();
+        let d = Dmp::new();
         let mut patches = d.patch_make1(&c.text1, &c.text2);
         assert_eq!(
             d.patch_to_text(&mut patches),
@@ -126,7 +144,7 @@ fn golden_patch_make() {
 #[test]
 fn golden_patch_text_roundtrip() {
     for c in corpus() {
-        let mut d = Dmp::new();
+        let d = Dmp::new();
         let mut patches = d.patch_from_text(c.patch_text.clone());
         assert_eq!(
             d.patch_to_text(&mut patches),
@@ -140,7 +158,7 @@ fn golden_patch_text_roundtrip() {
 #[test]
 fn golden_patch_apply() {
     for c in corpus() {
-        let mut d = Dmp::new();
+        let d = Dmp::new();
         let mut patches = d.patch_from_text(c.patch_text.clone());
         let (applied, results) = d.patch_apply(&mut patches, &c.apply_to);
         let applied: String = applied.into_iter().collect();
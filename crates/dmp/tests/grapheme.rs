@@ -9,7 +9,7 @@ use diff_match_patch::{Diff, Dmp, Segmentation};
 fn tuples(diffs: &[Diff]) -> Vec<(i32, String)> {
     diffs
         .iter()
-        .map(|d| (d.operation, d.text.clone()))
+        .map(|d| (d.operation.into(), d.text.clone()))
         .collect()
 }
 
@@ -22,7 +22,7 @@ fn grapheme_diff_never_splits_zwj_cluster() {
     let t2 = "a\u{1F469}\u{200D}\u{1F469}\u{200D}\u{1F467}b"; // a👩‍👩‍👧b
 
     // Char mode (the default) splits inside the ZWJ sequence.
-    let mut char_mode = Dmp::new();
+    let char_mode = Dmp::new();
     let char_diffs = char_mode.diff_main(t1, t2, false);
     assert_eq!(
         tuples(&char_diffs),
@@ -81,7 +81,7 @@ fn grapheme_mode_matches_char_mode_on_single_char_clusters() {
     let t1 = "The quick brown fox jumps over the lazy dog.";
     let t2 = "The quick brown cat jumps over the sleepy dog.";
 
-    let mut char_mode = Dmp::new();
+    let char_mode = Dmp::new();
     let mut grapheme_mode = Dmp::new();
     grapheme_mode.segmentation = Segmentation::Grapheme;
     assert_eq!(
@@ -133,7 +133,7 @@ fn grapheme_cleanup_semantic_respects_clusters() {
     };
 
     // Char mode extracts the shared regional indicator, splitting both flags.
-    let mut char_mode = Dmp::new();
+    let char_mode = Dmp::new();
     let mut char_diffs = make();
     char_mode.diff_cleanup_semantic(&mut char_diffs);
     assert_eq!(
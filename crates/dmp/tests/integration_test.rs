@@ -1,13 +1,14 @@
+use diff_match_patch::Operation;
 use std::collections::HashMap;
 
 pub fn diff_rebuildtexts(diffs: Vec<diff_match_patch::Diff>) -> Vec<String> {
     let mut text1: String = "".to_string();
     let mut text2: String = "".to_string();
     for diff in &diffs {
-        if diff.operation != 1 {
+        if diff.operation != Operation::Insert {
             text1 += diff.text.as_str();
         }
-        if diff.operation != -1 {
+        if diff.operation != Operation::Delete {
             text2 += diff.text.as_str();
         }
     }
@@ -17,7 +18,7 @@ pub fn diff_rebuildtexts(diffs: Vec<diff_match_patch::Diff>) -> Vec<String> {
 
 #[test]
 pub fn test_diff_common_prefix() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     assert_eq!(
         0,
         dmp.diff_common_prefix(
@@ -45,7 +46,7 @@ pub fn test_diff_common_prefix() {
 
 #[test]
 pub fn test_diff_common_suffix() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     assert_eq!(
         0,
         dmp.diff_common_suffix(
@@ -73,7 +74,7 @@ pub fn test_diff_common_suffix() {
 
 #[test]
 pub fn test_diff_common_overlap() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     assert_eq!(
         0,
         dmp.diff_common_overlap(
@@ -200,7 +201,7 @@ pub fn test_diff_half_match_no_timeout() {
 
 #[test]
 pub fn test_diff_lines_tochars() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     assert_eq!(
         (
             "\x01\x02\x01".to_string(),
@@ -262,7 +263,7 @@ pub fn test_diff_lines_tochars() {
 
 #[test]
 pub fn test_diff_words_tochars() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     assert_eq!(
         (
             "\x01\x02\x03\x02\x01".to_string(),
@@ -321,7 +322,7 @@ pub fn test_diff_words_tochars() {
 
 #[test]
 pub fn test_diff_chars_tolines() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let mut diffs = vec![
         diff_match_patch::Diff::new(0, "\x01\x02\x01".to_string()),
         diff_match_patch::Diff::new(1, "\x02\x01\x02".to_string()),
@@ -357,7 +358,7 @@ pub fn test_diff_chars_tolines() {
 
 #[test]
 pub fn diff_lines_tochars_munge() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
 
     // Unicode codepoints from 55296 to 57344 are reserved and can't be used as a scalar
     let number_of_lines = 60000;
@@ -381,7 +382,7 @@ pub fn diff_lines_tochars_munge() {
 
 #[test]
 pub fn test_diff_cleanup_merge() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let mut diffs: Vec<diff_match_patch::Diff> = vec![];
     let temp: Vec<diff_match_patch::Diff> = vec![];
     dmp.diff_cleanup_merge(&mut diffs);
@@ -593,7 +594,7 @@ pub fn test_diff_cleanup_merge() {
 pub fn test_diff_cleanup_semantic_lossless() {
     // Slide diffs to match logical boundaries.
     // Null case.
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let mut diffs: Vec<diff_match_patch::Diff> = vec![];
     let temp: Vec<diff_match_patch::Diff> = vec![];
     dmp.diff_cleanup_semantic_lossless(&mut diffs);
@@ -712,7 +713,7 @@ pub fn test_diff_cleanup_semantic_lossless() {
 
 #[test]
 pub fn test_diff_cleanup_semantic() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
 
     //  Null case.
     let mut diffs: Vec<diff_match_patch::Diff> = vec![];
@@ -995,7 +996,7 @@ pub fn test_diff_cleanup_efficiency() {
 
 #[test]
 pub fn test_diff_text() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let mut diffs: Vec<diff_match_patch::Diff> = vec![
         diff_match_patch::Diff::new(0, "jump".to_string()),
         diff_match_patch::Diff::new(-1, "s".to_string()),
@@ -1014,7 +1015,7 @@ pub fn test_diff_text() {
 
 #[test]
 pub fn test_diff_delta() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let mut diffs = vec![
         diff_match_patch::Diff::new(0, "jump".to_string()),
         diff_match_patch::Diff::new(-1, "s".to_string()),
@@ -1114,7 +1115,7 @@ pub fn test_diff_delta() {
 
 #[test]
 pub fn test_diff_delta_surrogates() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
 
     // Inserting similar surrogate pair at beginning
     let mut diffs = dmp.diff_main("🅰🅱", "🅱🅰🅱", false);
@@ -1161,7 +1162,7 @@ pub fn test_diff_delta_surrogates() {
 
 #[test]
 pub fn test_diff_to_delta_astral() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
 
     // Lengths count Unicode scalars: one astral char is 1, not 2.
     let mut diffs = dmp.diff_main("🅰", "🅱", false);
@@ -1171,7 +1172,7 @@ pub fn test_diff_to_delta_astral() {
 
 #[test]
 pub fn test_diff_from_delta_astral() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
 
     // Lengths count Unicode scalars: one astral char is 1, not 2.
     let mut diffs = dmp.diff_from_delta("🅰🅲", "-1\t=1\t+%F0%9F%85%B1");
@@ -1180,7 +1181,7 @@ pub fn test_diff_from_delta_astral() {
 
 #[test]
 pub fn test_diff_xindex() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
 
     // Translate a location in text1 to text2.
     let mut diffs = vec![
@@ -1201,7 +1202,7 @@ pub fn test_diff_xindex() {
 
 #[test]
 pub fn test_diff_levenshtein() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     assert_eq!(
         4,
         dmp.diff_levenshtein(&mut vec![
@@ -1232,7 +1233,7 @@ pub fn test_diff_levenshtein() {
 
 #[test]
 pub fn test_diff_bisect() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let a = "cat".to_string();
     let b = "map".to_string();
     assert_eq!(
@@ -1267,7 +1268,7 @@ pub fn test_diff_bisect_timeout() {
 
 #[test]
 pub fn test_diff_main() {
-    let mut new_dmp = diff_match_patch::Dmp::new();
+    let new_dmp = diff_match_patch::Dmp::new();
     let temp: Vec<diff_match_patch::Diff> = Vec::new();
     assert_eq!(temp, new_dmp.diff_main("", "", true));
     assert_eq!(
@@ -1413,7 +1414,7 @@ pub fn test_diff_main() {
 
 #[test]
 pub fn test_match_alphabet() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let mut s: HashMap<char, i32> = HashMap::new();
     s.insert('a', 4);
     s.insert('b', 2);
@@ -1644,7 +1645,7 @@ pub fn test_patch_obj() {
 
 #[test]
 pub fn test_patch_from_text() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let diffs: Vec<diff_match_patch::Patch> = vec![];
     assert_eq!(diffs, dmp.patch_from_text("".to_string()));
 
@@ -1669,7 +1670,7 @@ pub fn test_patch_from_text() {
 
 #[test]
 pub fn test_patch_to_text() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let mut strp = "@@ -21,18 +22,17 @@\n jump\n-s\n+ed\n  over \n-the\n+a\n  laz\n".to_string();
     let mut p = dmp.patch_from_text(strp.clone());
     assert_eq!(strp, dmp.patch_to_text(&mut p));
@@ -1734,7 +1735,7 @@ pub fn test_patch_add_context() {
 
 #[test]
 pub fn test_patch_make() {
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     // Null case.
     let mut patches = dmp.patch_make1("", "");
     assert_eq!("".to_string(), dmp.patch_to_text(&mut patches));
@@ -1824,7 +1825,7 @@ pub fn test_patch_splitmax() {
 #[test]
 pub fn test_patch_add_padding() {
     // Both edges full.
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let mut patches = dmp.patch_make1("", "test");
     assert_eq!(
         "@@ -0,0 +1,4 @@\n+test\n".to_string(),
@@ -2042,7 +2043,7 @@ pub fn test_match_bitap_maxbits_pattern() {
 pub fn test_diff_words_tochars_unicode_whitespace() {
     // Word-mode splits on Unicode whitespace, one token per whitespace char;
     // pins the regex-\s semantics across the manual-scanner rewrite.
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let t1 = "a\u{00A0}b\u{2003}c\u{3000}d\te f".to_string();
     let t2 = "a\u{00A0}b".to_string();
     let (_c1, _c2, words) = dmp.diff_words_tochars(&t1, &t2);
@@ -2070,7 +2071,7 @@ pub fn test_diff_words_tochars_unicode_whitespace() {
 pub fn test_patch_from_text_multibyte_sign_line() {
     // A body line starting with a multi-byte char must fail as an invalid
     // patch mode, not as a byte-boundary panic inside the parser.
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let _ = dmp.patch_from_text("@@ -1 +1 @@\n\u{e9}x\n".to_string());
 }
 
@@ -2078,7 +2079,7 @@ pub fn test_patch_from_text_multibyte_sign_line() {
 pub fn test_match_bitap_pattern_at_text_end() {
     // The exact-match speedup probes rfind_sub at loc + pattern.len() ==
     // text.len(); the pre-rewrite rkmp indexed out of bounds there.
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let text: Vec<char> = "ab".chars().collect();
     let pattern: Vec<char> = "b".chars().collect();
     assert_eq!(dmp.match_bitap(&text, &pattern, 1), 1);
@@ -2113,7 +2114,7 @@ pub fn test_match_bitap_no_limit_oversize_pattern_panics() {
 pub fn test_patch_from_text_trusts_header_lengths() {
     // Like the oracle, the parser keeps header lengths verbatim instead of
     // recomputing them from the body (the pre-rewrite parser recomputed).
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let patches = dmp.patch_from_text("@@ -1,99 +1,99 @@\n-a\n+b\n x\n".to_string());
     assert_eq!(patches[0].length1, 99);
     assert_eq!(patches[0].length2, 99);
@@ -2124,6 +2125,1451 @@ pub fn test_patch_from_text_trusts_header_lengths() {
 pub fn test_patch_from_text_rejects_wrapping_coordinates() {
     // Coordinates beyond i32 must error like the oracle rejects non-digits,
     // never wrap negative.
-    let mut dmp = diff_match_patch::Dmp::new();
+    let dmp = diff_match_patch::Dmp::new();
     let _ = dmp.patch_from_text("@@ -3000000000,4 +3000000000,4 @@\n x\n".to_string());
 }
+
+#[test]
+pub fn test_operation_integer_codes() {
+    // The enum keeps the historical wire codes, and integer callers map by
+    // sign so pre-enum code keeps building while it migrates.
+    assert_eq!(i32::from(Operation::Delete), -1);
+    assert_eq!(i32::from(Operation::Equal), 0);
+    assert_eq!(i32::from(Operation::Insert), 1);
+    assert_eq!(Operation::from(-1), Operation::Delete);
+    assert_eq!(Operation::from(0), Operation::Equal);
+    assert_eq!(Operation::from(1), Operation::Insert);
+    // `From` is lossy; `from_code` rejects anything but the three codes.
+    assert_eq!(Operation::from(2), Operation::Insert);
+    assert_eq!(Operation::from_code(-1), Some(Operation::Delete));
+    assert_eq!(Operation::from_code(0), Some(Operation::Equal));
+    assert_eq!(Operation::from_code(1), Some(Operation::Insert));
+    assert_eq!(Operation::from_code(2), None);
+    assert_eq!(Operation::from_code(-2), None);
+    assert_eq!(
+        diff_match_patch::Diff::new(-1, "x".to_string()),
+        diff_match_patch::Diff::new(Operation::Delete, "x".to_string())
+    );
+}
+
+#[test]
+pub fn test_try_diff_from_delta() {
+    use diff_match_patch::ParseError;
+    let dmp = diff_match_patch::Dmp::new();
+    let diffs = vec![
+        diff_match_patch::Diff::new(Operation::Equal, "jump".to_string()),
+        diff_match_patch::Diff::new(Operation::Delete, "s".to_string()),
+        diff_match_patch::Diff::new(Operation::Insert, "ed".to_string()),
+    ];
+    assert_eq!(Ok(diffs), dmp.try_diff_from_delta("jumps", "=4\t-1\t+ed"));
+
+    // Each failure names the token where parsing stopped.
+    assert_eq!(
+        Err(ParseError::LengthMismatch {
+            index: 1,
+            delta_length: 6,
+            text_length: 5
+        }),
+        dmp.try_diff_from_delta("jumps", "=4\t-2\t+ed")
+    );
+    assert_eq!(
+        Err(ParseError::LengthMismatch {
+            index: 2,
+            delta_length: 4,
+            text_length: 5
+        }),
+        dmp.try_diff_from_delta("jumps", "=4\t+ed")
+    );
+    assert_eq!(
+        Err(ParseError::InvalidLength {
+            index: 0,
+            text: "x".to_string()
+        }),
+        dmp.try_diff_from_delta("jumps", "=x")
+    );
+    assert_eq!(
+        Err(ParseError::IllegalEscape {
+            index: 1,
+            text: "%c3%xy".to_string()
+        }),
+        dmp.try_diff_from_delta("", "\t+%c3%xy")
+    );
+    assert_eq!(
+        Err(ParseError::InvalidOperation {
+            index: 0,
+            text: "*5".to_string()
+        }),
+        dmp.try_diff_from_delta("jumps", "*5")
+    );
+}
+
+#[test]
+pub fn test_try_patch_from_text() {
+    use diff_match_patch::ParseError;
+    let dmp = diff_match_patch::Dmp::new();
+    let text = "@@ -21,18 +22,17 @@\n jump\n-s\n+ed\n  over \n";
+    assert_eq!(
+        dmp.try_patch_from_text(text).unwrap(),
+        dmp.patch_from_text(text.to_string())
+    );
+    assert_eq!(Ok(vec![]), dmp.try_patch_from_text(""));
+
+    // Each failure names the line where parsing stopped.
+    assert_eq!(
+        Err(ParseError::BadHeader {
+            index: 0,
+            text: "Bad".to_string()
+        }),
+        dmp.try_patch_from_text("Bad\nPatch\n")
+    );
+    assert_eq!(
+        Err(ParseError::BadCoordinates {
+            index: 2,
+            text: "x,2".to_string()
+        }),
+        dmp.try_patch_from_text("@@ -1 +1 @@\n a\n@@ -x,2 +1 @@\n")
+    );
+    assert_eq!(
+        Err(ParseError::InvalidMode {
+            index: 1,
+            text: "*a".to_string()
+        }),
+        dmp.try_patch_from_text("@@ -1 +1 @@\n*a\n")
+    );
+    assert_eq!(
+        Err(ParseError::IllegalEscape {
+            index: 2,
+            text: "%ff".to_string()
+        }),
+        dmp.try_patch_from_text("@@ -1 +1 @@\n a\n+%ff\n")
+    );
+    // The message is what the panicking parser reports.
+    let err = dmp.try_patch_from_text("@@ -1 +1 @@\n*a\n").unwrap_err();
+    assert_eq!(err.to_string(), "Invalid patch mode at line 1: *a");
+}
+
+#[test]
+pub fn test_diff_pretty_html() {
+    let dmp = diff_match_patch::Dmp::new();
+    let diffs = vec![
+        diff_match_patch::Diff::new(Operation::Equal, "a\n".to_string()),
+        diff_match_patch::Diff::new(Operation::Delete, "<B>b</B>".to_string()),
+        diff_match_patch::Diff::new(Operation::Insert, "c&d".to_string()),
+    ];
+    assert_eq!(
+        "<span>a&para;<br></span><del style=\"background:#ffe6e6;\">&lt;B&gt;b&lt;/B&gt;</del><ins style=\"background:#e6ffe6;\">c&amp;d</ins>",
+        dmp.diff_pretty_html(&diffs)
+    );
+
+    // Classes replace the inline styles; tags and the newline marker are configurable.
+    let options = diff_match_patch::HtmlOptions {
+        equal_tag: "span".to_string(),
+        delete_tag: "s".to_string(),
+        insert_tag: "u".to_string(),
+        delete_class: Some("old".to_string()),
+        equal_class: Some("same \"x\"".to_string()),
+        insert_class: Some("new".to_string()),
+        pilcrow: false,
+    };
+    assert_eq!(
+        "<span class=\"same &quot;x&quot;\">a<br></span><s class=\"old\">&lt;B&gt;b&lt;/B&gt;</s><u class=\"new\">c&amp;d</u>",
+        dmp.diff_pretty_html_with(&diffs, &options).unwrap()
+    );
+
+    // Tags that are not plain element names are refused.
+    for tag in ["", "1b", "b onclick=x", "b><script", "b\""] {
+        let options = diff_match_patch::HtmlOptions {
+            insert_tag: tag.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            Err(diff_match_patch::MarkupError::InvalidTag {
+                tag: tag.to_string()
+            }),
+            dmp.diff_pretty_html_with(&diffs, &options)
+        );
+    }
+    let options = diff_match_patch::HtmlOptions {
+        insert_tag: "my-ins".to_string(),
+        ..Default::default()
+    };
+    assert!(dmp.diff_pretty_html_with(&diffs, &options).is_ok());
+}
+
+#[test]
+pub fn test_diff_pretty_inline() {
+    let dmp = diff_match_patch::Dmp::new();
+    let diffs = vec![
+        diff_match_patch::Diff::new(Operation::Equal, "The quick".to_string()),
+        diff_match_patch::Diff::new(Operation::Delete, " brown\n".to_string()),
+        diff_match_patch::Diff::new(Operation::Insert, "\tred".to_string()),
+        diff_match_patch::Diff::new(Operation::Equal, " fox\x1b jumps".to_string()),
+    ];
+    let plain = diff_match_patch::InlineOptions::default();
+    assert_eq!(
+        "The quick[- brown\n-]{+\tred+} fox\x1b jumps",
+        dmp.diff_pretty_wdiff(&diffs, &plain)
+    );
+    // Colors close before a line break and reopen after it.
+    assert_eq!(
+        "The quick\x1b[31m brown\x1b[0m\n\x1b[32m\tred\x1b[0m fox\x1b jumps",
+        dmp.diff_pretty_ansi(&diffs, &plain)
+    );
+
+    let options = diff_match_patch::InlineOptions {
+        show_whitespace: true,
+        show_control: true,
+        context: Some(3),
+    };
+    assert_eq!(
+        "…ick[-·brown↵\n-]{+→red+} fo…",
+        dmp.diff_pretty_wdiff(&diffs, &options)
+    );
+    assert_eq!(
+        "\x1b[2m…\x1b[0mick\x1b[31m·brown↵\x1b[0m\n\x1b[32m→red\x1b[0m fo\x1b[2m…\x1b[0m",
+        dmp.diff_pretty_ansi(&diffs, &options)
+    );
+    // Control pictures apply everywhere; whitespace marks only inside edits.
+    let options = diff_match_patch::InlineOptions {
+        show_control: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        "The quick[- brown\n-]{+\tred+} fox␛ jumps",
+        dmp.diff_pretty_wdiff(&diffs, &options)
+    );
+
+    // Middle equalities keep context on both sides; short ones stay whole.
+    let diffs = vec![
+        diff_match_patch::Diff::new(Operation::Delete, "a".to_string()),
+        diff_match_patch::Diff::new(Operation::Equal, "0123456789".to_string()),
+        diff_match_patch::Diff::new(Operation::Insert, "b".to_string()),
+        diff_match_patch::Diff::new(Operation::Equal, "xyz".to_string()),
+    ];
+    let options = diff_match_patch::InlineOptions {
+        context: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(
+        "[-a-]01…89{+b+}xy…",
+        dmp.diff_pretty_wdiff(&diffs, &options)
+    );
+    let options = diff_match_patch::InlineOptions {
+        context: Some(3),
+        ..Default::default()
+    };
+    assert_eq!(
+        "[-a-]012…789{+b+}xyz",
+        dmp.diff_pretty_wdiff(&diffs, &options)
+    );
+
+    // An unchanged text borders no edit and is not elided.
+    let diffs = dmp.diff_main("hello world", "hello world", false);
+    assert_eq!("hello world", dmp.diff_pretty_wdiff(&diffs, &options));
+    assert_eq!("hello world", dmp.diff_pretty_ansi(&diffs, &options));
+
+    // Closing markers inside an edit are escaped.
+    let diffs = vec![
+        diff_match_patch::Diff::new(Operation::Delete, "x[-1-]".to_string()),
+        diff_match_patch::Diff::new(Operation::Insert, "y{+2+}".to_string()),
+    ];
+    assert_eq!(
+        "[-x[-1-\\]-]{+y{+2+\\}+}",
+        dmp.diff_pretty_wdiff(&diffs, &plain)
+    );
+}
+
+#[test]
+pub fn test_diff_unified() {
+    let dmp = diff_match_patch::Dmp::new();
+    let options = diff_match_patch::UnifiedOptions::default();
+    // Identical texts: no output at all.
+    assert_eq!("", dmp.diff_unified("a\nb\n", "a\nb\n", &options));
+
+    // Changes more than 2 * context lines apart get separate hunks; a final
+    // line without newline is marked (byte-identical to `diff -u`).
+    let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+    let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nJ";
+    assert_eq!(
+        "--- a\n+++ b\n@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n@@ -7,4 +7,4 @@\n g\n h\n i\n-j\n+J\n\\ No newline at end of file\n",
+        dmp.diff_unified(old, new, &options)
+    );
+
+    // Wider context merges them; labels are free text.
+    let options = diff_match_patch::UnifiedOptions {
+        context: 4,
+        old_label: "old.txt\t2024-01-01".to_string(),
+        new_label: "new.txt".to_string(),
+        no_newline_marker: false,
+    };
+    assert_eq!(
+        "--- old.txt\t2024-01-01\n+++ new.txt\n@@ -1,10 +1,10 @@\n a\n-b\n+B\n c\n d\n e\n f\n g\n h\n i\n-j\n+J\n",
+        dmp.diff_unified(old, new, &options)
+    );
+
+    // Empty ranges anchor on the preceding line; single-line ranges drop ",1".
+    let options = diff_match_patch::UnifiedOptions {
+        context: 0,
+        ..Default::default()
+    };
+    assert_eq!(
+        "--- a\n+++ b\n@@ -1,0 +2 @@\n+x\n@@ -3 +3,0 @@\n-c\n",
+        dmp.diff_unified("a\nb\nc\n", "a\nx\nb\n", &options)
+    );
+    assert_eq!(
+        "--- a\n+++ b\n@@ -0,0 +1,2 @@\n+x\n+y\n",
+        dmp.diff_unified("", "x\ny\n", &options)
+    );
+}
+
+#[test]
+pub fn test_unified_from_text() {
+    let dmp = diff_match_patch::Dmp::new();
+    let text = "diff --git a/x.txt b/x.txt\nindex 1234..5678 100644\n--- a/x.txt\n+++ b/x.txt\n@@ -1,3 +1,3 @@ fn main\n one\n-two\n+TWO\n three\n--- a/y.txt\t2024-01-01\n+++ b/y.txt\n@@ -1 +1 @@\n--- old\n\\ No newline at end of file\n+new\n@@ -3,0 +4,2 @@\n+\n+end\n";
+    let files = dmp.unified_from_text(text).unwrap();
+    assert_eq!(2, files.len());
+    assert_eq!("a/x.txt", files[0].old_label);
+    assert_eq!("b/x.txt", files[0].new_label);
+    assert_eq!(
+        vec![diff_match_patch::UnifiedHunk {
+            old_start: 0,
+            old_len: 3,
+            new_start: 0,
+            new_len: 3,
+            diffs: vec![
+                diff_match_patch::Diff::new(Operation::Equal, "one\n".to_string()),
+                diff_match_patch::Diff::new(Operation::Delete, "two\n".to_string()),
+                diff_match_patch::Diff::new(Operation::Insert, "TWO\n".to_string()),
+                diff_match_patch::Diff::new(Operation::Equal, "three\n".to_string()),
+            ],
+        }],
+        files[0].hunks
+    );
+    // Labels are verbatim; body lines are read by count, so "--- old" is a
+    // deletion, not a file header.
+    assert_eq!("a/y.txt\t2024-01-01", files[1].old_label);
+    assert_eq!(
+        vec![
+            diff_match_patch::Diff::new(Operation::Delete, "-- old".to_string()),
+            diff_match_patch::Diff::new(Operation::Insert, "new\n".to_string()),
+        ],
+        files[1].hunks[0].diffs
+    );
+    assert_eq!(
+        (3, 0, 3, 2),
+        (
+            files[1].hunks[1].old_start,
+            files[1].hunks[1].old_len,
+            files[1].hunks[1].new_start,
+            files[1].hunks[1].new_len
+        )
+    );
+
+    assert_eq!(
+        Err(diff_match_patch::ParseError::BadHeader {
+            index: 0,
+            text: "@@ -1 +1 @@".to_string()
+        }),
+        dmp.unified_from_text("@@ -1 +1 @@\n-a\n+b\n")
+    );
+    assert_eq!(
+        Err(diff_match_patch::ParseError::HunkLength {
+            index: 2,
+            text: "@@ -1,2 +1 @@".to_string()
+        }),
+        dmp.unified_from_text("--- a\n+++ b\n@@ -1,2 +1 @@\n-a\n+b\n")
+    );
+    assert_eq!(
+        Err(diff_match_patch::ParseError::InvalidMode {
+            index: 4,
+            text: "*b".to_string()
+        }),
+        dmp.unified_from_text("--- a\n+++ b\n@@ -1,2 +1,2 @@\n a\n*b\n")
+    );
+}
+
+#[test]
+pub fn test_patch_apply_unified() {
+    let dmp = diff_match_patch::Dmp::new();
+    let old = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+    let new = "one\nTWO\nthree\nfour\nfive\nsix\nseven\neight\nnine\nTEN\n";
+    let text = dmp.diff_unified(old, new, &diff_match_patch::UnifiedOptions::default());
+    let files = dmp.unified_from_text(&text).unwrap();
+    assert_eq!(
+        (new.to_string(), vec![true, true]),
+        dmp.patch_apply_unified(&files[0], old)
+    );
+
+    // The hunks are found by fuzzy search, not by line number: lines added
+    // above and a typo in the context do not stop them.
+    let drifted = "zero\nminus one\none\ntwo\nthree\nfuor\nfive\nsix\nseven\neight\nnine\nten\n";
+    assert_eq!(
+        (
+            "zero\nminus one\none\nTWO\nthree\nfuor\nfive\nsix\nseven\neight\nnine\nTEN\n"
+                .to_string(),
+            vec![true, true]
+        ),
+        dmp.patch_apply_unified(&files[0], drifted)
+    );
+
+    // A hunk whose text is gone fails alone.
+    assert_eq!(
+        (
+            "one\nTWO\nthree\nfour\nfive\nsix\nqqqqq\nxxxxx\nyyyy\nzzz\n".to_string(),
+            vec![true, false]
+        ),
+        dmp.patch_apply_unified(
+            &files[0],
+            "one\ntwo\nthree\nfour\nfive\nsix\nqqqqq\nxxxxx\nyyyy\nzzz\n"
+        )
+    );
+
+    // A hunk longer than match_maxbits is applied in pieces; if one piece
+    // fails, none of it is kept.
+    let old = "start\nalpha line number one\nbravo line number two\nend\n";
+    let new = "start\nALPHA\nBRAVO\nend\n";
+    let text = dmp.diff_unified(old, new, &diff_match_patch::UnifiedOptions::default());
+    let files = dmp.unified_from_text(&text).unwrap();
+    let target = "start\nalpha line number one\n0123456789 0123456789 01\nend\n";
+    assert_eq!(
+        (target.to_string(), vec![false]),
+        dmp.patch_apply_unified(&files[0], target)
+    );
+
+    // Context-free hunks (`diff -U0`) whose old lines are not where the
+    // header says are not padded with whatever text is there, and the next
+    // hunk still finds its own line.
+    let lines =
+        |edit: &dyn Fn(usize) -> String| -> String { (1..=30).map(|i| edit(i) + "\n").collect() };
+    let old = lines(&|i| format!("line number {i}"));
+    let new = lines(&|i| match i {
+        3 => "X".to_string(),
+        4 => "Y".to_string(),
+        26 => "changed".to_string(),
+        _ => format!("line number {i}"),
+    });
+    let options = diff_match_patch::UnifiedOptions {
+        context: 0,
+        ..Default::default()
+    };
+    let files = dmp
+        .unified_from_text(&dmp.diff_unified(&old, &new, &options))
+        .unwrap();
+    assert_eq!(2, files[0].hunks.len());
+    let target = lines(&|i| match i {
+        4 => "the fourth line, rewritten".to_string(),
+        _ => format!("line number {i}"),
+    });
+    let (patched, results) = dmp.patch_apply_unified(&files[0], &target);
+    assert_eq!(vec![false, true], results);
+    assert_eq!(target.replace("line number 26\n", "changed\n"), patched);
+
+    // The next hunk's line follows where the one before it landed, not the
+    // header's line counts: with three lines added on top, the second hunk
+    // must edit the second "dup" (line 16), not the first one at its
+    // nominal line 13.
+    let line = |i: usize| match i {
+        10 | 13 => "dup".to_string(),
+        _ => format!("line number {i}"),
+    };
+    let old = lines(&line);
+    let new = lines(&|i| match i {
+        2 => "two".to_string(),
+        13 => "changed".to_string(),
+        _ => line(i),
+    });
+    let files = dmp
+        .unified_from_text(&dmp.diff_unified(&old, &new, &options))
+        .unwrap();
+    assert_eq!(2, files[0].hunks.len());
+    let target = format!("a\nb\nc\n{old}");
+    assert_eq!(
+        (format!("a\nb\nc\n{new}"), vec![true, true]),
+        dmp.patch_apply_unified(&files[0], &target)
+    );
+}
+
+#[test]
+pub fn test_diff_slices() {
+    use diff_match_patch::SeqDiff;
+    let dmp = diff_match_patch::Dmp::new();
+    // Any Eq + Hash item works, e.g. records.
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    struct Row(&'static str, u32);
+    let old = [Row("a", 1), Row("b", 2), Row("c", 3), Row("d", 4)];
+    let new = [
+        Row("a", 1),
+        Row("b", 20),
+        Row("c", 3),
+        Row("e", 5),
+        Row("d", 4),
+    ];
+    assert_eq!(
+        vec![
+            SeqDiff::new(Operation::Equal, vec![Row("a", 1)]),
+            SeqDiff::new(Operation::Delete, vec![Row("b", 2)]),
+            SeqDiff::new(Operation::Insert, vec![Row("b", 20)]),
+            SeqDiff::new(Operation::Equal, vec![Row("c", 3)]),
+            SeqDiff::new(Operation::Insert, vec![Row("e", 5)]),
+            SeqDiff::new(Operation::Equal, vec![Row("d", 4)]),
+        ],
+        dmp.diff_slices(&old, &new)
+    );
+
+    // Trivial cases.
+    let empty: [u32; 0] = [];
+    assert_eq!(Vec::<SeqDiff<u32>>::new(), dmp.diff_slices(&empty, &empty));
+    assert_eq!(
+        vec![SeqDiff::new(Operation::Equal, vec![1, 2])],
+        dmp.diff_slices(&[1, 2], &[1, 2])
+    );
+    assert_eq!(
+        vec![SeqDiff::new(Operation::Insert, vec![1, 2])],
+        dmp.diff_slices(&empty, &[1, 2])
+    );
+    // Containment and a replaced single item.
+    assert_eq!(
+        vec![
+            SeqDiff::new(Operation::Delete, vec![0]),
+            SeqDiff::new(Operation::Equal, vec![1, 2]),
+            SeqDiff::new(Operation::Delete, vec![3]),
+        ],
+        dmp.diff_slices(&[0, 1, 2, 3], &[1, 2])
+    );
+    assert_eq!(
+        vec![
+            SeqDiff::new(Operation::Delete, vec![7]),
+            SeqDiff::new(Operation::Insert, vec![8, 9]),
+        ],
+        dmp.diff_slices(&[7], &[8, 9])
+    );
+}
+
+#[test]
+pub fn test_diff_bytes() {
+    use diff_match_patch::SeqDiff;
+    let dmp = diff_match_patch::Dmp::new();
+    // Latin-1 "café" → "cafe!", not valid UTF-8.
+    let old = b"caf\xE9";
+    let new = b"cafe!";
+    let diffs = dmp.diff_bytes(old, new);
+    assert_eq!(
+        vec![
+            SeqDiff::new(Operation::Equal, b"caf".to_vec()),
+            SeqDiff::new(Operation::Delete, vec![0xE9]),
+            SeqDiff::new(Operation::Insert, b"e!".to_vec()),
+        ],
+        diffs
+    );
+
+    // Lengths count bytes; inserted bytes are escaped one by one.
+    let diffs = dmp.diff_bytes(b"a\xFFb", b"a\x80\xFFb c");
+    let delta = dmp.diff_todelta_bytes(&diffs);
+    assert_eq!("=1\t+%80\t=2\t+ c", delta);
+    assert_eq!(diffs, dmp.diff_from_delta_bytes(b"a\xFFb", &delta).unwrap());
+    assert_eq!(
+        Err(diff_match_patch::ParseError::LengthMismatch {
+            index: 4,
+            delta_length: 3,
+            text_length: 4
+        }),
+        dmp.diff_from_delta_bytes(b"a\xFFbc", &delta)
+    );
+
+    // Byte patches: ASCII-only text, raw bytes back.
+    let patches = dmp.patch_make_bytes(old, new);
+    let text = dmp.patch_to_text_bytes(&patches);
+    assert_eq!("@@ -1,4 +1,5 @@\n caf\n-%E9\n+e!\n", text);
+    let patches = dmp.patch_from_text_bytes(&text).unwrap();
+    assert_eq!(
+        (new.to_vec(), vec![true]),
+        dmp.patch_apply_bytes(&patches, old)
+    );
+    // Fuzzy location works on bytes as on text.
+    let patches = dmp.patch_make_bytes(b"le caf\xE9 noir", b"le cafe! noir");
+    assert_eq!(
+        (b"\x00\x01 le cafe! noir \xFE".to_vec(), vec![true]),
+        dmp.patch_apply_bytes(&patches, b"\x00\x01 le caf\xE9 noir \xFE")
+    );
+}
+
+#[test]
+pub fn test_diff_main_ref() {
+    use diff_match_patch::DiffRef;
+    let dmp = diff_match_patch::Dmp::new();
+    let text1 = "The caf\u{e9} is open.";
+    let text2 = "The caf\u{e9} was open.";
+    let mut diffs = dmp.diff_main_ref(text1, text2, false);
+    assert_eq!(
+        vec![
+            DiffRef::new(Operation::Equal, "The caf\u{e9} "),
+            DiffRef::new(Operation::Delete, "i"),
+            DiffRef::new(Operation::Insert, "wa"),
+            DiffRef::new(Operation::Equal, "s open."),
+        ],
+        diffs
+    );
+    // Chunks point into the inputs rather than copies.
+    assert!(std::ptr::eq(diffs[0].text.as_ptr(), text1.as_ptr()));
+    assert!(std::ptr::eq(diffs[2].text.as_ptr(), text2[10..].as_ptr()));
+
+    assert_eq!(text1, dmp.diff_text1(&mut diffs));
+    assert_eq!(text2, dmp.diff_text2(&mut diffs));
+    assert_eq!(2, dmp.diff_levenshtein(&diffs));
+    assert_eq!("=9\t-1\t+wa\t=7", dmp.diff_todelta(&mut diffs));
+
+    assert_eq!(Vec::<DiffRef>::new(), dmp.diff_main_ref("", "", true));
+    assert_eq!(
+        vec![DiffRef::new(Operation::Equal, "abc")],
+        dmp.diff_main_ref("abc", "abc", true)
+    );
+}
+
+#[test]
+pub fn test_diff_lines() {
+    use diff_match_patch::{Diff, Line, LineHunk, LineOptions, Refine};
+    let dmp = diff_match_patch::Dmp::new();
+    let old = "a\nb\nc\nd\ne\nf\ng\nthe old line\n";
+    let new = "a\nB\nc\nd\ne\nf\ng\nthe new line\nextra\n";
+    let line = |operation, old_index, new_index, text: &str| Line {
+        operation,
+        old_index,
+        new_index,
+        text: text.to_string(),
+        highlights: vec![],
+    };
+    let options = LineOptions {
+        context: 1,
+        refine: Refine::None,
+    };
+    assert_eq!(
+        vec![
+            LineHunk {
+                old_start: 0,
+                old_len: 3,
+                new_start: 0,
+                new_len: 3,
+                lines: vec![
+                    line(Operation::Equal, Some(0), Some(0), "a\n"),
+                    line(Operation::Delete, Some(1), None, "b\n"),
+                    line(Operation::Insert, None, Some(1), "B\n"),
+                    line(Operation::Equal, Some(2), Some(2), "c\n"),
+                ],
+            },
+            LineHunk {
+                old_start: 6,
+                old_len: 2,
+                new_start: 6,
+                new_len: 3,
+                lines: vec![
+                    line(Operation::Equal, Some(6), Some(6), "g\n"),
+                    line(Operation::Delete, Some(7), None, "the old line\n"),
+                    line(Operation::Insert, None, Some(7), "the new line\n"),
+                    line(Operation::Insert, None, Some(8), "extra\n"),
+                ],
+            },
+        ],
+        dmp.diff_lines(old, new, &options)
+    );
+    assert!(dmp.diff_lines(old, old, &LineOptions::default()).is_empty());
+
+    // Replaced line pairs get intra-line highlights; the unpaired insertion
+    // does not.
+    let options = LineOptions {
+        context: 0,
+        refine: Refine::Words,
+    };
+    let hunks = dmp.diff_lines(old, new, &options);
+    assert_eq!(2, hunks.len());
+    let lines = &hunks[1].lines;
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Equal, "the ".to_string()),
+            Diff::new(Operation::Delete, "old".to_string()),
+            Diff::new(Operation::Equal, " line\n".to_string()),
+        ],
+        lines[0].highlights
+    );
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Equal, "the ".to_string()),
+            Diff::new(Operation::Insert, "new".to_string()),
+            Diff::new(Operation::Equal, " line\n".to_string()),
+        ],
+        lines[1].highlights
+    );
+    assert!(lines[2].highlights.is_empty());
+
+    // Char refinement highlights just the changed chars.
+    let options = LineOptions {
+        context: 0,
+        refine: Refine::Chars,
+    };
+    let hunks = dmp.diff_lines("colour = 1\n", "color = 2\n", &options);
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Equal, "colo".to_string()),
+            Diff::new(Operation::Delete, "u".to_string()),
+            Diff::new(Operation::Equal, "r = ".to_string()),
+            Diff::new(Operation::Delete, "1".to_string()),
+            Diff::new(Operation::Equal, "\n".to_string()),
+        ],
+        hunks[0].lines[0].highlights
+    );
+}
+
+#[test]
+pub fn test_diff_tokens() {
+    use diff_match_patch::{Diff, LineTokenizer, SentenceTokenizer, Tokenizer, WordTokenizer};
+    let dmp = diff_match_patch::Dmp::new();
+
+    // Lines: a changed line is replaced whole.
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Equal, "one\n".to_string()),
+            Diff::new(Operation::Delete, "two\n".to_string()),
+            Diff::new(Operation::Insert, "too\n".to_string()),
+            Diff::new(Operation::Equal, "three".to_string()),
+        ],
+        dmp.diff_tokens("one\ntwo\nthree", "one\ntoo\nthree", &LineTokenizer)
+    );
+
+    // Whitespace words: the diff_words vocabulary.
+    assert_eq!(
+        vec!["a", "\r\n", "b-c", " \u{301}", " ", "d"],
+        WordTokenizer.tokenize("a\r\nb-c \u{301} d")
+    );
+    let (old, new) = ("Price: $19.99 — In stock", "Price: $24.99 — Sold out");
+    assert_eq!(
+        dmp.diff_words(old, new),
+        dmp.diff_tokens(old, new, &WordTokenizer)
+    );
+
+    // Sentences keep their trailing whitespace.
+    assert_eq!(
+        vec!["It rained. ", "Then it stopped."],
+        SentenceTokenizer.tokenize("It rained. Then it stopped.")
+    );
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Equal, "It rained. ".to_string()),
+            Diff::new(Operation::Delete, "Then it stopped.".to_string()),
+            Diff::new(Operation::Insert, "It kept going.".to_string()),
+        ],
+        dmp.diff_tokens(
+            "It rained. Then it stopped.",
+            "It rained. It kept going.",
+            &SentenceTokenizer
+        )
+    );
+
+    // Tokenizers work through a trait object too.
+    let tokenizer: &dyn Tokenizer = &LineTokenizer;
+    assert!(dmp.diff_tokens("", "", tokenizer).is_empty());
+}
+
+#[cfg(feature = "grapheme")]
+#[test]
+pub fn test_diff_tokens_unicode_words() {
+    use diff_match_patch::{Tokenizer, UnicodeWordTokenizer};
+    let dmp = diff_match_patch::Dmp::new();
+    assert_eq!(
+        vec!["ABC", "-", "123", " ", "$", "24.99"],
+        UnicodeWordTokenizer.tokenize("ABC-123 $24.99")
+    );
+    let diffs = dmp.diff_tokens("the dog.", "the dogs.", &UnicodeWordTokenizer);
+    assert_eq!(
+        "the [-dog-]{+dogs+}.",
+        dmp.diff_pretty_wdiff(&diffs, &Default::default())
+    );
+}
+
+#[test]
+#[should_panic(expected = "tokens must concatenate back to the text")]
+pub fn test_diff_tokens_rejects_lossy_tokenizer() {
+    struct Lossy;
+    impl diff_match_patch::Tokenizer for Lossy {
+        fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+            text.split_whitespace().collect()
+        }
+    }
+    diff_match_patch::Dmp::new().diff_tokens("a b", "a c", &Lossy);
+}
+
+#[test]
+pub fn test_diff_options() {
+    use diff_match_patch::{DiffOptions, Dmp};
+    fn assert_shareable<T: Clone + Send + Sync>() {}
+    assert_shareable::<DiffOptions>();
+
+    let mut base = Dmp::new();
+    base.diff_timeout = Some(1.0);
+    let a = "The quick brown fox jumps over the lazy dog.";
+    let b = "That quick brown fox jumped over a lazy dog.";
+
+    // No overrides reproduces the plain methods.
+    let opts = DiffOptions::new();
+    let expected = Dmp::new().diff_main(a, b, true);
+    assert_eq!(expected, base.diff_main_with(&opts, a, b));
+    assert_eq!(
+        Dmp::new().patch_make1(a, b),
+        base.patch_make_with(&opts, a, b)
+    );
+
+    // Overrides apply to the call only; the shared Dmp is untouched.
+    let opts = DiffOptions::new().diff_timeout(None).edit_cost(4);
+    base.diff_main_with(&opts, a, b);
+    assert_eq!(Some(1.0), base.diff_timeout);
+    assert_eq!(0, base.edit_cost);
+
+    // Match settings: an exact-location search.
+    let exact = DiffOptions::new().match_distance(0).match_threshold(0.0);
+    assert_eq!(-1, base.match_main_with(&exact, "abcdefghijk", "efxhi", 4));
+    assert_eq!(
+        4,
+        base.match_main_with(&DiffOptions::new(), "abcdefghijk", "efxhi", 4)
+    );
+
+    // Patch settings: a looser delete threshold accepts a sloppy deletion.
+    let patches = base.patch_make_with(
+        &DiffOptions::new(),
+        "x1234567890123456789012345678901234567890123456789012345678901234567890y",
+        "xabcy",
+    );
+    let sloppy =
+        "x12345678901234567890---------------++++++++++---------------12345678901234567890y";
+    let (_, applied) = base.patch_apply_with(&DiffOptions::new(), &patches, sloppy);
+    assert_eq!(vec![false, true], applied);
+    let loose = DiffOptions::new().patch_delete_threshold(0.6);
+    let (_, applied) = base.patch_apply_with(&loose, &patches, sloppy);
+    assert_eq!(vec![true, true], applied);
+
+    // One config serves concurrent callers.
+    let shared = Dmp::new();
+    let opts = DiffOptions::new().checklines(false);
+    std::thread::scope(|s| {
+        for i in 0..4 {
+            let (shared, opts) = (&shared, &opts);
+            s.spawn(move || {
+                let new = format!("{a} #{i}");
+                let diffs = shared.diff_words_with(opts, a, &new);
+                assert_eq!(Operation::Insert, diffs.last().unwrap().operation);
+            });
+        }
+    });
+}
+
+#[test]
+pub fn test_shared_dmp() {
+    // One Dmp serves several threads through a shared reference.
+    let dmp = std::sync::Arc::new(diff_match_patch::Dmp::new());
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let dmp = std::sync::Arc::clone(&dmp);
+            std::thread::spawn(move || {
+                let new = format!("The quick brown fox #{i}.");
+                let mut diffs = dmp.diff_main("The quick brown fox.", &new, true);
+                assert_eq!(new, dmp.diff_text2(&mut diffs));
+                let mut patches = dmp.patch_make1("The quick brown fox.", &new);
+                let (patched, applied) = dmp.patch_apply(&mut patches, "The quick brown fox.");
+                assert_eq!(vec![true], applied);
+                assert_eq!(new, patched.into_iter().collect::<String>());
+                dmp.diff_levenshtein(&diffs)
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(3, handle.join().unwrap());
+    }
+}
+
+#[test]
+pub fn test_diff_main_outcome() {
+    use diff_match_patch::{CancelToken, Diff, DiffOptions, Dmp, Progress, Truncation};
+    use std::sync::{Arc, Mutex};
+
+    let dmp = Dmp::new();
+    // Runs to completion.
+    let opts = DiffOptions::new().checklines(false);
+    let outcome = dmp.diff_main_outcome(&opts, "cat", "map");
+    assert!(outcome.is_exact());
+    assert_eq!(dmp.diff_main("cat", "map", false), outcome.diffs);
+
+    // Cancelled up front: the bisect stops at once, leaving a coarse but
+    // valid diff.
+    let token = CancelToken::new();
+    token.cancel();
+    let cancelled = opts.clone().cancel(token);
+    let outcome = dmp.diff_main_outcome(&cancelled, "cat", "map");
+    assert_eq!(Some(Truncation::Cancelled), outcome.truncated);
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Delete, "cat".to_string()),
+            Diff::new(Operation::Insert, "map".to_string()),
+        ],
+        outcome.diffs
+    );
+
+    // An expired timeout is reported as such.
+    let outcome = dmp.diff_main_outcome(&opts.clone().diff_timeout(Some(0.0)), "cat", "map");
+    assert_eq!(Some(Truncation::Deadline), outcome.truncated);
+    assert_eq!(
+        vec!["cat".to_string(), "map".to_string()],
+        diff_rebuildtexts(outcome.diffs)
+    );
+
+    // Progress only grows and ends with everything settled.
+    let mut a = String::new();
+    let mut b = String::new();
+    for i in 0..300 {
+        a += &format!("line {i}: the quick brown fox\n");
+        b += &format!(
+            "line {i}: the quick {} fox\n",
+            if i % 7 == 0 { "red" } else { "brown" }
+        );
+    }
+    let seen: Arc<Mutex<Vec<Progress>>> = Arc::default();
+    let sink = Arc::clone(&seen);
+    let opts = DiffOptions::new()
+        .diff_timeout(None)
+        .progress(move |p| sink.lock().unwrap().push(p));
+    let outcome = dmp.diff_main_outcome(&opts, &a, &b);
+    assert!(outcome.is_exact());
+    assert_eq!(vec![a.clone(), b.clone()], diff_rebuildtexts(outcome.diffs));
+    let seen = seen.lock().unwrap();
+    let total = a.len() + b.len();
+    assert!(seen.len() > 10);
+    assert!(seen.windows(2).all(|w| w[0].bytes_done < w[1].bytes_done));
+    assert!(seen.iter().all(|p| p.bytes_total == total));
+    assert_eq!(total, seen.last().unwrap().bytes_done);
+}
+
+#[test]
+pub fn test_with_entry_points_cancel_and_progress() {
+    use diff_match_patch::{CancelToken, Diff, DiffOptions, Dmp, Progress};
+    use std::sync::{Arc, Mutex};
+
+    let dmp = Dmp::new();
+    let token = CancelToken::new();
+    token.cancel();
+    let cancelled = DiffOptions::new().checklines(false).cancel(token);
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Delete, "cat".to_string()),
+            Diff::new(Operation::Insert, "map".to_string()),
+        ],
+        dmp.diff_main_with(&cancelled, "cat", "map")
+    );
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Delete, "cat sat".to_string()),
+            Diff::new(Operation::Insert, "map sit".to_string()),
+        ],
+        dmp.diff_words_with(&cancelled, "cat sat", "map sit")
+    );
+    let patches = dmp.patch_make_with(&cancelled, "cat", "map");
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Delete, "cat".to_string()),
+            Diff::new(Operation::Insert, "map".to_string()),
+        ],
+        patches[0].diffs
+    );
+
+    // Progress counts input bytes whatever the diff runs on: chars, words
+    // or grapheme clusters.
+    let seen: Arc<Mutex<Vec<Progress>>> = Arc::default();
+    let sink = Arc::clone(&seen);
+    let opts = DiffOptions::new().progress(move |p| sink.lock().unwrap().push(p));
+    let (old, new) = ("naïve café au lait", "naïve cafe\u{301} au laît");
+    dmp.diff_main_with(&opts, old, new);
+    dmp.diff_words_with(&opts, old, new);
+    #[cfg(feature = "grapheme")]
+    {
+        let opts = opts
+            .segmentation(diff_match_patch::Segmentation::Grapheme)
+            .word_vocabulary(diff_match_patch::WordVocabulary::Unicode);
+        dmp.diff_main_with(&opts, old, new);
+        dmp.diff_words_with(&opts, old, new);
+    }
+    let seen = seen.lock().unwrap();
+    let ends: Vec<&Progress> = seen
+        .iter()
+        .filter(|p| p.bytes_done == p.bytes_total)
+        .collect();
+    assert_eq!(if cfg!(feature = "grapheme") { 4 } else { 2 }, ends.len());
+    assert!(ends.iter().all(|p| p.bytes_total == old.len() + new.len()));
+}
+
+#[test]
+pub fn test_diff_stats() {
+    use diff_match_patch::{DiffOptions, Dmp};
+
+    let dmp = Dmp::new();
+    let opts = DiffOptions::new().checklines(false);
+    // A genuine rewrite: the bisect finds nothing in common.
+    let rewrite = dmp.diff_main_outcome(&opts, "abc", "xyz");
+    assert!(rewrite.is_exact());
+    assert!(!rewrite.stats.deadline_hit);
+    assert_eq!(1, rewrite.stats.bisects);
+    assert!(rewrite.stats.ascii_fast_path);
+    // The same shape of result, but because time ran out.
+    let timed_out = dmp.diff_main_outcome(&opts.clone().diff_timeout(Some(0.0)), "cat", "map");
+    assert!(timed_out.stats.deadline_hit);
+    assert_eq!(2, timed_out.diffs.len());
+
+    // Line mode engages on large multi-line inputs; non-ASCII text takes the
+    // char path.
+    let a = "head α\n".to_string() + &"Ünïcode line\n".repeat(60) + "tail α\n";
+    let b = "head β\n".to_string() + &"Ünïcode line\n".repeat(60) + "tail β\n";
+    let outcome = dmp.diff_main_outcome(&DiffOptions::new().diff_timeout(None), &a, &b);
+    assert_eq!(1, outcome.stats.line_mode_passes);
+    assert_eq!(0, outcome.stats.word_mode_passes);
+    assert!(!outcome.stats.ascii_fast_path);
+    assert!(!outcome.stats.grapheme_packed);
+
+    #[cfg(feature = "grapheme")]
+    {
+        let opts = DiffOptions::new().segmentation(diff_match_patch::Segmentation::Grapheme);
+        let outcome = dmp.diff_main_outcome(&opts, "cafe\u{301}", "cafe");
+        assert!(outcome.stats.grapheme_packed);
+    }
+}
+
+#[test]
+pub fn test_diff_budget() {
+    use diff_match_patch::{DiffOptions, Dmp, Truncation};
+
+    let mut a = String::new();
+    let mut b = String::new();
+    for i in 0..200 {
+        a += &format!("row {i}: alpha beta gamma {}\n", i * 7 % 13);
+        b += &format!("row {i}: alpha BETA gamma {}\n", i * 5 % 11);
+    }
+    // A tight budget truncates, identically on every run.
+    let mut dmp = Dmp::new();
+    dmp.diff_budget = Some(500);
+    let first = dmp.diff_main(&a, &b, false);
+    assert_eq!(first, dmp.diff_main(&a, &b, false));
+    assert_eq!(vec![a.clone(), b.clone()], diff_rebuildtexts(first.clone()));
+    let outcome = Dmp::new().diff_main_outcome(
+        &DiffOptions::new().checklines(false).diff_budget(Some(500)),
+        &a,
+        &b,
+    );
+    assert_eq!(Some(Truncation::Budget), outcome.truncated);
+    assert_eq!(first, outcome.diffs);
+    assert!(outcome.stats.steps <= 500);
+
+    // With no budget at all, the line-level pass and its rediff both stop
+    // at once: only the trimmed prefix and suffix survive.
+    let outcome = Dmp::new().diff_main_outcome(&DiffOptions::new().diff_budget(Some(0)), &a, &b);
+    assert_eq!(Some(Truncation::Budget), outcome.truncated);
+    assert_eq!(0, outcome.stats.steps);
+    assert!(outcome.diffs.len() <= 4);
+    assert_eq!(vec![a.clone(), b.clone()], diff_rebuildtexts(outcome.diffs));
+
+    // A generous budget runs to completion.
+    dmp.diff_budget = Some(u64::MAX);
+    let outcome = dmp.diff_main_outcome(&DiffOptions::new(), &a, &b);
+    assert!(outcome.is_exact());
+    assert!(outcome.stats.steps > 500);
+}
+
+#[test]
+pub fn test_diff_patience() {
+    use diff_match_patch::{Diff, DiffAlgorithm, DiffOptions, Dmp, LineOptions, Truncation};
+
+    // Two functions swap places. Myers keeps their shared braces and blank
+    // lines and rewrites everything between them; patience anchors on the
+    // unique lines and moves one function whole.
+    let copy = "void Chunk_copy(Chunk *src, size_t n)\n{\n    if (!check(src, n)) return;\n\n    memcpy(dst, src, n);\n}\n";
+    let check = "int check(Chunk *chunk, size_t n)\n{\n    if (chunk == NULL) return 0;\n\n    return n <= chunk->length;\n}\n";
+    let old = format!("{copy}\n{check}");
+    let new = format!("{check}\n{copy}");
+    let mut dmp = Dmp::new();
+    dmp.diff_algorithm = DiffAlgorithm::Patience;
+    let hunks = dmp.diff_lines(&old, &new, &LineOptions::default());
+    let kept: String = hunks
+        .iter()
+        .flat_map(|h| &h.lines)
+        .filter(|l| l.operation == Operation::Equal)
+        .map(|l| l.text.as_str())
+        .collect();
+    assert!(copy.contains(&kept) && kept.len() > 30, "{kept:?}");
+    let changed: Vec<Operation> = hunks
+        .iter()
+        .flat_map(|h| &h.lines)
+        .map(|l| l.operation)
+        .filter(|&op| op != Operation::Equal)
+        .collect();
+    assert_eq!(vec![Operation::Insert; 7], changed[..7]);
+    assert_eq!(vec![Operation::Delete; 7], changed[7..]);
+
+    // diff_main takes the same alignment when checklines is on.
+    let diffs = dmp.diff_main(&old, &new, true);
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Insert, format!("{check}\n")),
+            Diff::new(Operation::Equal, copy.to_string()),
+            Diff::new(Operation::Delete, format!("\n{check}")),
+        ],
+        diffs
+    );
+    let opts = DiffOptions::new().algorithm(DiffAlgorithm::Patience);
+    assert_eq!(diffs, Dmp::new().diff_main_with(&opts, &old, &new));
+
+    // The anchor search is charged against the budget; with none left the
+    // lines are replaced whole.
+    let outcome = Dmp::new().diff_main_outcome(&opts.clone().diff_budget(Some(0)), &old, &new);
+    assert_eq!(Some(Truncation::Budget), outcome.truncated);
+    assert!(!outcome
+        .diffs
+        .iter()
+        .any(|d| d.operation == Operation::Equal && d.text.contains(check)));
+    assert_eq!(
+        vec![old.clone(), new.clone()],
+        diff_rebuildtexts(outcome.diffs)
+    );
+    let outcome = Dmp::new().diff_main_outcome(&opts.diff_timeout(Some(0.0)), &old, &new);
+    assert_eq!(Some(Truncation::Deadline), outcome.truncated);
+}
+
+#[test]
+pub fn test_diff_histogram() {
+    use diff_match_patch::{Diff, DiffAlgorithm, DiffOptions, Dmp, Truncation};
+
+    // Like patience, histogram moves a swapped function whole.
+    let copy = "void Chunk_copy(Chunk *src, size_t n)\n{\n    if (!check(src, n)) return;\n\n    memcpy(dst, src, n);\n}\n";
+    let check = "int check(Chunk *chunk, size_t n)\n{\n    if (chunk == NULL) return 0;\n\n    return n <= chunk->length;\n}\n";
+    let old = format!("{copy}\n{check}");
+    let new = format!("{check}\n{copy}");
+    let opts = DiffOptions::new().algorithm(DiffAlgorithm::Histogram);
+    let dmp = Dmp::new();
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Delete, format!("{copy}\n")),
+            Diff::new(Operation::Equal, check.to_string()),
+            Diff::new(Operation::Insert, format!("\n{copy}")),
+        ],
+        dmp.diff_main_with(&opts, &old, &new)
+    );
+
+    // It honors the same limits as the Myers path.
+    let outcome = dmp.diff_main_outcome(&opts.clone().diff_budget(Some(0)), &old, &new);
+    assert_eq!(Some(Truncation::Budget), outcome.truncated);
+    assert_eq!(
+        vec![old.clone(), new.clone()],
+        diff_rebuildtexts(outcome.diffs)
+    );
+    let outcome = dmp.diff_main_outcome(&opts.diff_timeout(Some(0.0)), &old, &new);
+    assert_eq!(Some(Truncation::Deadline), outcome.truncated);
+}
+
+#[test]
+pub fn test_patch_apply_report() {
+    use diff_match_patch::{ApplyFailure, ApplyMethod, Dmp};
+
+    let mut dmp = Dmp::new();
+    dmp.match_distance = 1000;
+    dmp.match_threshold = 0.5;
+    dmp.patch_delete_threshold = 0.5;
+    let old = "The quick brown fox jumps over the lazy dog.";
+    let patches = dmp.patch_make1(old, "That quick brown fox jumped over a lazy dog.");
+
+    // Exact match, in place.
+    let (text, reports) = dmp.patch_apply_report(&patches, old);
+    assert_eq!("That quick brown fox jumped over a lazy dog.", text);
+    assert!(reports.iter().all(|r| r.is_exact()));
+    assert_eq!(Some(0.0), reports[0].score);
+    assert_eq!(reports[0].actual, Some(reports[0].expected));
+
+    // Drifted: the second patch is found verbatim, four chars later.
+    let (text, reports) =
+        dmp.patch_apply_report(&patches, "The quick big brown fox jumps over the lazy dog.");
+    assert_eq!("That quick big brown fox jumped over a lazy dog.", text);
+    assert!(reports[0].is_exact());
+    assert_eq!(Some(4), reports[1].delta);
+    assert_eq!(Some(ApplyMethod::Splice), reports[1].method);
+    assert!(reports[1].score.unwrap() > 0.0);
+    assert!(!reports[1].is_exact());
+
+    // Fuzzy: located, then merged through a diff.
+    let (text, reports) =
+        dmp.patch_apply_report(&patches, "The quick red rabbit jumps over the tired tiger.");
+    assert_eq!("That quick red rabbit jumped over a tired tiger.", text);
+    assert!(reports.iter().all(|r| r.applied()));
+    assert_eq!(Some(ApplyMethod::Diff), reports[1].method);
+
+    // No match anywhere.
+    let (_, reports) =
+        dmp.patch_apply_report(&patches, "I am the very model of a modern major general.");
+    assert_eq!(Some(ApplyFailure::NoMatch), reports[0].failure);
+    assert_eq!(None, reports[0].actual);
+    assert_eq!(None, reports[0].score);
+
+    // Both ends of a big delete found, but too much changed between them.
+    let patches = dmp.patch_make1(
+        "x1234567890123456789012345678901234567890123456789012345678901234567890y",
+        "xabcy",
+    );
+    let (_, reports) = dmp.patch_apply_report(
+        &patches,
+        "x12345678901234567890---------------++++++++++---------------12345678901234567890y",
+    );
+    assert_eq!(Some(ApplyFailure::OverDeleteThreshold), reports[0].failure);
+    assert_eq!(Some(ApplyMethod::Diff), reports[0].method);
+    assert!(reports[1].applied());
+
+    // The start of a big delete found, its end missing.
+    dmp.match_threshold = 0.1;
+    let (_, reports) = dmp.patch_apply_report(
+        &patches,
+        "x1234567890123456789012345678901234567890ABCDEFGHIJKLMNOPQRSTUVWXYZABCDEFGH",
+    );
+    assert_eq!(Some(ApplyFailure::BadTrailingContext), reports[0].failure);
+}
+
+#[test]
+pub fn test_patch_apply_policy() {
+    use diff_match_patch::{ApplyFailure, ApplyPolicy, DiffOptions, Dmp};
+
+    let mut dmp = Dmp::new();
+    let old = "The quick brown fox jumps over the lazy dog.";
+    let new = "That quick brown fox jumped over a lazy dog.";
+    let patches = dmp.patch_make1(old, new);
+    let drifted = "The quick big brown fox jumps over the lazy dog.";
+    let fuzzy = "The quick red rabbit jumps over the tired tiger.";
+
+    // Exact at the expected location.
+    dmp.patch_apply_policy = ApplyPolicy::ExactAt;
+    let (text, reports) = dmp.patch_apply_report(&patches, old);
+    assert_eq!(new, text);
+    assert!(reports.iter().all(|r| r.is_exact()));
+    let (text, reports) = dmp.patch_apply_report(&patches, drifted);
+    assert_eq!("That quick big brown fox jumps over the lazy dog.", text);
+    assert!(reports[0].applied());
+    assert_eq!(Some(ApplyFailure::NotAtLocation), reports[1].failure);
+    assert_eq!(None, reports[1].actual);
+
+    // Exact, within a bounded offset.
+    dmp.patch_apply_policy = ApplyPolicy::ExactWithin(4);
+    let (text, reports) = dmp.patch_apply_report(&patches, drifted);
+    assert_eq!("That quick big brown fox jumped over a lazy dog.", text);
+    assert_eq!(Some(4), reports[1].delta);
+    dmp.patch_apply_policy = ApplyPolicy::ExactWithin(3);
+    let (_, reports) = dmp.patch_apply_report(&patches, drifted);
+    assert_eq!(Some(ApplyFailure::NotWithinOffset), reports[1].failure);
+    // Exact modes never merge into changed text.
+    let (text, reports) = dmp.patch_apply_report(&patches, fuzzy);
+    assert_eq!(fuzzy, text);
+    assert!(!reports.iter().any(|r| r.applied()));
+
+    // Fuzzy (the default) finds and merges all three.
+    let dmp = Dmp::new();
+    for text in [old, drifted, fuzzy] {
+        let (_, applied) = dmp.patch_apply(&mut patches.clone(), text);
+        assert_eq!(vec![true, true], applied);
+    }
+
+    // Per call, through DiffOptions.
+    let strict = DiffOptions::new().apply_policy(ApplyPolicy::ExactAt);
+    let (text, applied) = dmp.patch_apply_with(&strict, &patches, drifted);
+    assert_eq!("That quick big brown fox jumps over the lazy dog.", text);
+    assert_eq!(vec![true, false], applied);
+}
+
+#[test]
+pub fn test_patch_apply_str() {
+    use diff_match_patch::Dmp;
+
+    let dmp = Dmp::new();
+    let patches = dmp.patch_make1(
+        "The quick brown fox jumps over the lazy dog.",
+        "That quick brown fox jumped over a lazy dog.",
+    );
+    assert_eq!(
+        (
+            "That quick red rabbit jumped over a tired tiger.".to_string(),
+            vec![true, true]
+        ),
+        dmp.patch_apply_str(&patches, "The quick red rabbit jumps over the tired tiger.")
+    );
+
+    // Non-ASCII text takes the char path.
+    let mut text = "The quick brown fox jumps over the lazy dog. \u{1F98A}".to_string();
+    assert_eq!(
+        vec![true, true],
+        dmp.patch_apply_in_place(&patches, &mut text)
+    );
+    assert_eq!(
+        "That quick brown fox jumped over a lazy dog. \u{1F98A}",
+        text
+    );
+
+    // Failed patches leave the text alone.
+    let mut text = "I am the very model of a modern major general.".to_string();
+    assert_eq!(
+        vec![false, false],
+        dmp.patch_apply_in_place(&patches, &mut text)
+    );
+    assert_eq!("I am the very model of a modern major general.", text);
+}
+
+#[test]
+pub fn test_patch_invert() {
+    use diff_match_patch::Dmp;
+
+    let dmp = Dmp::new();
+    let old = "The quick brown fox jumps over the lazy dog.";
+    let new = "That quick brown fox jumped over a lazy dog.";
+    let patches = dmp.patch_make1(old, new);
+    let mut inverse = dmp.patch_invert(&patches);
+    assert_eq!(
+        "@@ -1,12 +1,11 @@\n Th\n-at\n+e\n  quick b\n@@ -21,17 +21,18 @@\n jump\n-ed\n+s\n  over \n-a\n+the\n  laz\n",
+        dmp.patch_to_text(&mut inverse)
+    );
+    assert_eq!(
+        (old.to_string(), vec![true, true]),
+        dmp.patch_apply_str(&inverse, new)
+    );
+
+    // Undo after the text has moved on.
+    assert_eq!(
+        (
+            "Oh, the quick brown fox jumps over the lazy dog.".to_string(),
+            vec![true, true]
+        ),
+        dmp.patch_apply_str(&inverse, "Oh, that quick brown fox jumped over a lazy dog.")
+    );
+}
+
+#[test]
+pub fn test_compose() {
+    use diff_match_patch::{ComposeError, Diff, Dmp, Patch};
+
+    let dmp = Dmp::new();
+    // Text inserted by the first diff and deleted by the second cancels.
+    let a = vec![
+        Diff::new(Operation::Equal, "The ".to_string()),
+        Diff::new(Operation::Insert, "quick ".to_string()),
+        Diff::new(Operation::Equal, "fox".to_string()),
+    ];
+    let b = vec![
+        Diff::new(Operation::Equal, "The ".to_string()),
+        Diff::new(Operation::Delete, "quick fox".to_string()),
+        Diff::new(Operation::Insert, "dog".to_string()),
+    ];
+    assert_eq!(
+        vec![
+            Diff::new(Operation::Equal, "The ".to_string()),
+            Diff::new(Operation::Delete, "fox".to_string()),
+            Diff::new(Operation::Insert, "dog".to_string()),
+        ],
+        dmp.diff_compose(&a, &b).unwrap()
+    );
+    // A deletion put back is no change at all.
+    let undo = vec![
+        Diff::new(Operation::Equal, "The ".to_string()),
+        Diff::new(Operation::Delete, "quick ".to_string()),
+        Diff::new(Operation::Equal, "fox".to_string()),
+    ];
+    assert_eq!(
+        vec![Diff::new(Operation::Equal, "The quick fox".to_string())],
+        dmp.diff_compose(&undo, &a).unwrap()
+    );
+
+    // A version chain folds into one patch list.
+    let versions = [
+        "The quick brown fox jumps over the lazy dog.",
+        "The quick brown fox jumped over the lazy dog.",
+        "A quick brown fox jumped over the lazy dog!",
+        "A quick red fox jumped over the lazy cat!",
+    ];
+    let composed = versions
+        .windows(2)
+        .map(|pair| dmp.patch_make1(pair[0], pair[1]))
+        .reduce(|a, b| dmp.patch_compose(&a, &b).unwrap())
+        .unwrap();
+    assert_eq!(
+        (versions[3].to_string(), vec![true; composed.len()]),
+        dmp.patch_apply_str(&composed, versions[0])
+    );
+
+    // Patches followed by their inverse compose to nothing.
+    let patches = dmp.patch_make1(versions[0], versions[3]);
+    assert!(dmp
+        .patch_compose(&patches, &dmp.patch_invert(&patches))
+        .unwrap()
+        .is_empty());
+
+    // A second edit that does not start from the first one's new text.
+    let other = vec![
+        Diff::new(Operation::Equal, "The ".to_string()),
+        Diff::new(Operation::Delete, "slow fox".to_string()),
+    ];
+    assert_eq!(
+        Err(ComposeError::Mismatch { offset: 4 }),
+        dmp.diff_compose(&a, &other)
+    );
+    let short = vec![Diff::new(Operation::Equal, "The quick".to_string())];
+    assert_eq!(
+        Err(ComposeError::Mismatch { offset: 9 }),
+        dmp.diff_compose(&a, &short)
+    );
+    let first = dmp.patch_make1(versions[0], versions[1]);
+    let unrelated = dmp.patch_make1(
+        "The quick brown fox walks over the lazy dog.",
+        "The quick brown fox ran over the lazy dog.",
+    );
+    assert!(matches!(
+        dmp.patch_compose(&first, &unrelated),
+        Err(ComposeError::Mismatch { .. })
+    ));
+
+    // A patch changing text the patch before it already covers.
+    let mut overlapping = dmp.patch_make1("abcdef", "abXdef");
+    let mut second = Patch::new(vec![], 1, 1, 0, 0);
+    second.diffs = vec![
+        Diff::new(Operation::Delete, "b".to_string()),
+        Diff::new(Operation::Equal, "X".to_string()),
+    ];
+    second.length1 = 2;
+    second.length2 = 1;
+    overlapping.push(second);
+    assert_eq!(
+        Err(ComposeError::Overlap {
+            second: false,
+            index: 1
+        }),
+        dmp.patch_compose(&overlapping, &[])
+    );
+    assert_eq!(
+        Err(ComposeError::Overlap {
+            second: true,
+            index: 1
+        }),
+        dmp.patch_compose(&[], &overlapping)
+    );
+}
+
+#[test]
+pub fn test_diff_options_eq() {
+    use diff_match_patch::{CancelToken, DiffOptions};
+
+    assert_eq!(DiffOptions::new(), DiffOptions::new());
+    assert_ne!(
+        DiffOptions::new(),
+        DiffOptions::new().diff_timeout(Some(1.0))
+    );
+    // Tokens and hooks compare by identity: clones are equal, separately
+    // made ones are not.
+    let token = CancelToken::new();
+    let opts = DiffOptions::new().cancel(token.clone()).progress(|_| {});
+    assert_eq!(opts, opts.clone());
+    assert_ne!(opts, DiffOptions::new().cancel(token).progress(|_| {}));
+    assert_ne!(
+        DiffOptions::new().cancel(CancelToken::new()),
+        DiffOptions::new().cancel(CancelToken::new())
+    );
+}
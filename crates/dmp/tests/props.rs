@@ -1,7 +1,7 @@
 //! Property tests over LCG-generated inputs (self-contained; no external
 //! fuzzing crate). Each run is deterministic from the fixed seeds.
 
-use diff_match_patch::{Diff, Dmp};
+use diff_match_patch::{Diff, Dmp, Operation};
 
 struct Lcg(u64);
 impl Lcg {
@@ -18,10 +18,10 @@ fn rebuild(diffs: &[Diff]) -> (String, String) {
     let mut t1 = String::new();
     let mut t2 = String::new();
     for d in diffs {
-        if d.operation != 1 {
+        if d.operation != Operation::Insert {
             t1 += &d.text;
         }
-        if d.operation != -1 {
+        if d.operation != Operation::Delete {
             t2 += &d.text;
         }
     }
@@ -34,6 +34,20 @@ fn gen_text(rng: &mut Lcg, alphabet: &[&str], len: usize) -> String {
         .collect()
 }
 
+/// `text` with a few short runs replaced, far enough apart on longer texts
+/// to give patch lists of several patches.
+fn mutate(rng: &mut Lcg, alphabet: &[&str], text: &str) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    for _ in 0..(rng.next() % 4) {
+        let at = (rng.next() as usize) % (chars.len() + 1);
+        let cut = usize::min((rng.next() % 4) as usize, chars.len() - at);
+        let len = (rng.next() % 4) as usize;
+        let run: Vec<char> = gen_text(rng, alphabet, len).chars().collect();
+        chars.splice(at..at + cut, run);
+    }
+    chars.into_iter().collect()
+}
+
 /// Diffs reconstruct their inputs; deltas and patches round-trip (char mode).
 #[test]
 fn char_mode_roundtrips() {
@@ -44,7 +58,7 @@ fn char_mode_roundtrips() {
         let t1 = gen_text(&mut rng, &alphabet, len);
         let len = (rng.next() % 80) as usize;
         let t2 = gen_text(&mut rng, &alphabet, len);
-        let mut d = Dmp::new();
+        let d = Dmp::new();
         let mut diffs = d.diff_main(&t1, &t2, rng.next().is_multiple_of(2));
         assert_eq!(rebuild(&diffs), (t1.clone(), t2.clone()));
 
@@ -61,6 +75,338 @@ fn char_mode_roundtrips() {
     }
 }
 
+/// Borrowed diffs are exactly the owned ones, on multi-byte text too, and
+/// the diff-derived helpers agree on both forms.
+#[test]
+fn borrowed_diffs_match_owned() {
+    let alphabet = ["a", "b", "\n", " ", "\u{e9}", "\u{1F600}"];
+    let mut rng = Lcg(3141);
+    for _ in 0..400 {
+        let len = (rng.next() % 200) as usize;
+        let t1 = gen_text(&mut rng, &alphabet, len);
+        let len = (rng.next() % 200) as usize;
+        let t2 = gen_text(&mut rng, &alphabet, len);
+        let checklines = rng.next().is_multiple_of(2);
+        let d = Dmp::new();
+        let mut owned = d.diff_main(&t1, &t2, checklines);
+        let mut borrowed = d.diff_main_ref(&t1, &t2, checklines);
+        let converted: Vec<Diff> = borrowed.iter().map(|&r| r.into()).collect();
+        assert_eq!(converted, owned);
+        assert_eq!(d.diff_text1(&mut borrowed), t1);
+        assert_eq!(d.diff_text2(&mut borrowed), t2);
+        assert_eq!(d.diff_levenshtein(&borrowed), d.diff_levenshtein(&owned));
+        assert_eq!(d.diff_todelta(&mut borrowed), d.diff_todelta(&mut owned));
+    }
+}
+
+/// Sequence diffs rebuild their inputs, and without a timeout they are as
+/// short as the char diff of the same sequences (both are minimal).
+#[test]
+fn slice_diff_roundtrips() {
+    let alphabet = ["a", "b", "c", "\n", " "];
+    let mut rng = Lcg(77);
+    for _ in 0..400 {
+        let len = (rng.next() % 60) as usize;
+        let t1 = gen_text(&mut rng, &alphabet, len);
+        let len = (rng.next() % 60) as usize;
+        let t2 = gen_text(&mut rng, &alphabet, len);
+        let s1: Vec<char> = t1.chars().collect();
+        let s2: Vec<char> = t2.chars().collect();
+        let mut d = Dmp::new();
+        let seq = d.diff_slices(&s1, &s2);
+        let mut r1 = vec![];
+        let mut r2 = vec![];
+        for run in &seq {
+            assert!(!run.items.is_empty());
+            if run.operation != Operation::Insert {
+                r1.extend(&run.items);
+            }
+            if run.operation != Operation::Delete {
+                r2.extend(&run.items);
+            }
+        }
+        assert_eq!((r1, r2), (s1.clone(), s2.clone()));
+
+        d.diff_timeout = None;
+        let edits = |counts: Vec<(Operation, usize)>| -> usize {
+            counts
+                .iter()
+                .filter(|(op, _)| *op != Operation::Equal)
+                .map(|(_, n)| n)
+                .sum()
+        };
+        let seq = d.diff_slices(&s1, &s2);
+        let chars = d.diff_main(&t1, &t2, false);
+        assert_eq!(
+            edits(seq.iter().map(|r| (r.operation, r.items.len())).collect()),
+            edits(
+                chars
+                    .iter()
+                    .map(|r| (r.operation, r.text.chars().count()))
+                    .collect()
+            )
+        );
+    }
+}
+
+/// Byte diffs rebuild arbitrary (non-UTF-8) inputs, and byte deltas and
+/// patches round-trip through their wire formats.
+#[test]
+fn byte_mode_roundtrips() {
+    let alphabet = [0x00u8, b'a', b'b', b'\n', b'%', 0x80, 0xE9, 0xFF];
+    let mut rng = Lcg(808);
+    for _ in 0..400 {
+        let gen = |rng: &mut Lcg| -> Vec<u8> {
+            let len = (rng.next() % 300) as usize;
+            (0..len)
+                .map(|_| alphabet[(rng.next() as usize) % alphabet.len()])
+                .collect()
+        };
+        let b1 = gen(&mut rng);
+        let b2 = gen(&mut rng);
+        let d = Dmp::new();
+        let diffs = d.diff_bytes(&b1, &b2);
+        let mut r1 = vec![];
+        let mut r2 = vec![];
+        for run in &diffs {
+            if run.operation != Operation::Insert {
+                r1.extend(&run.items);
+            }
+            if run.operation != Operation::Delete {
+                r2.extend(&run.items);
+            }
+        }
+        assert_eq!((&r1, &r2), (&b1, &b2));
+
+        let delta = d.diff_todelta_bytes(&diffs);
+        assert_eq!(d.diff_from_delta_bytes(&b1, &delta).unwrap(), diffs);
+
+        let patches = d.patch_make_bytes(&b1, &b2);
+        let text = d.patch_to_text_bytes(&patches);
+        assert!(text.is_ascii());
+        let reparsed = d.patch_from_text_bytes(&text).unwrap();
+        assert_eq!(reparsed, patches);
+        let (applied, _) = d.patch_apply_bytes(&reparsed, &b1);
+        assert_eq!(applied, b2);
+    }
+}
+
+/// Unified diffs parse back into hunks that rebuild the new text, for every
+/// context size (including 0, where hunks carry no context to search by).
+#[test]
+fn unified_roundtrips() {
+    let alphabet = ["one\n", "two\n", "three\n", "\n", "x", "\u{e9}t\u{e9}\n"];
+    let mut rng = Lcg(2024);
+    for _ in 0..300 {
+        let len = (rng.next() % 30) as usize;
+        let t1 = gen_text(&mut rng, &alphabet, len);
+        let len = (rng.next() % 30) as usize;
+        let t2 = gen_text(&mut rng, &alphabet, len);
+        let d = Dmp::new();
+        let options = diff_match_patch::UnifiedOptions {
+            context: (rng.next() % 4) as usize,
+            ..Default::default()
+        };
+        let text = d.diff_unified(&t1, &t2, &options);
+        let files = d.unified_from_text(&text).unwrap();
+        if t1 == t2 {
+            assert!(files.is_empty());
+            continue;
+        }
+        assert_eq!(files.len(), 1);
+        let (applied, results) = d.patch_apply_unified(&files[0], &t1);
+        assert!(
+            results.iter().all(|&r| r),
+            "{:?} {:?} {:?}\n{}",
+            results,
+            t1,
+            t2,
+            text
+        );
+        assert_eq!(applied, t2, "{}", text);
+    }
+}
+
+/// Patience and histogram diffs reconstruct their inputs, both as a line
+/// diff and through `diff_main`'s char-level rediff.
+#[test]
+fn line_algorithms_roundtrip() {
+    let alphabet = ["{\n", "}\n", "\n", "a\n", "b\n", "c\n", "x", "\u{e9}\n"];
+    let mut rng = Lcg(4099);
+    let mut d = Dmp::new();
+    for i in 0..600 {
+        d.diff_algorithm = if i % 2 == 0 {
+            diff_match_patch::DiffAlgorithm::Patience
+        } else {
+            diff_match_patch::DiffAlgorithm::Histogram
+        };
+        let len = (rng.next() % 40) as usize;
+        let t1 = gen_text(&mut rng, &alphabet, len);
+        let len = (rng.next() % 40) as usize;
+        let t2 = gen_text(&mut rng, &alphabet, len);
+        let diffs = d.diff_main(&t1, &t2, true);
+        assert_eq!(rebuild(&diffs), (t1.clone(), t2.clone()));
+        let options = diff_match_patch::LineOptions {
+            context: usize::MAX,
+            ..Default::default()
+        };
+        let mut old = String::new();
+        let mut new = String::new();
+        for line in d
+            .diff_lines(&t1, &t2, &options)
+            .iter()
+            .flat_map(|h| &h.lines)
+        {
+            if line.operation != Operation::Insert {
+                old += &line.text;
+            }
+            if line.operation != Operation::Delete {
+                new += &line.text;
+            }
+        }
+        if t1 != t2 {
+            assert_eq!((old, new), (t1.clone(), t2.clone()));
+        }
+    }
+}
+
+/// `diff_words` cuts only between tokens: every piece boundary on either
+/// side is a grapheme cluster boundary with whitespace on at least one side.
+#[test]
+fn word_diffs_cut_between_tokens() {
+    use unicode_segmentation::UnicodeSegmentation;
+    let alphabet = ["a", "b", "\u{e9}", " ", "\r", "\n", "\t", "\u{301}", "-"];
+    let mut rng = Lcg(1109);
+    for _ in 0..400 {
+        let len = (rng.next() % 40) as usize;
+        let t1 = gen_text(&mut rng, &alphabet, len);
+        let len = (rng.next() % 40) as usize;
+        let t2 = gen_text(&mut rng, &alphabet, len);
+        let d = Dmp::new();
+        let diffs = d.diff_words(&t1, &t2);
+        assert_eq!(rebuild(&diffs), (t1.clone(), t2.clone()));
+        for (side, skip) in [(&t1, Operation::Insert), (&t2, Operation::Delete)] {
+            let pieces: Vec<&str> = diffs
+                .iter()
+                .filter(|d| d.operation != skip)
+                .map(|d| d.text.as_str())
+                .collect();
+            let clusters: usize = pieces.iter().map(|p| p.graphemes(true).count()).sum();
+            assert_eq!(clusters, side.graphemes(true).count(), "{:?}", diffs);
+            for pair in pieces.windows(2) {
+                let before = pair[0].graphemes(true).next_back().unwrap();
+                let after = pair[1].graphemes(true).next().unwrap();
+                let is_space = |c: &str| c.starts_with(char::is_whitespace);
+                assert!(is_space(before) || is_space(after), "{:?}", diffs);
+            }
+        }
+    }
+}
+
+/// `patch_apply_str` (bytes on ASCII input) and `patch_apply_in_place`
+/// agree with the char-based `patch_apply`, fuzzy matches included.
+#[test]
+fn str_apply_matches_char_apply() {
+    let ascii = ["a", "b", "c", "\n", " "];
+    let mixed = ["a", "b", "\u{e9}", "\n", " "];
+    let mut rng = Lcg(1213);
+    for i in 0..400 {
+        let alphabet = if i % 4 == 0 { &mixed } else { &ascii };
+        let len = (rng.next() % 120) as usize;
+        let t1 = gen_text(&mut rng, alphabet, len);
+        let len = (rng.next() % 120) as usize;
+        let t2 = gen_text(&mut rng, alphabet, len);
+        // The text the patches land on drifts from the one they were made
+        // against, so some patches need the fuzzy path or fail outright.
+        let mut target = t1.clone();
+        let at = (rng.next() as usize) % (target.len() + 1);
+        if target.is_char_boundary(at) {
+            let len = (rng.next() % 6) as usize;
+            target.insert_str(at, &gen_text(&mut rng, alphabet, len));
+        }
+        let d = Dmp::new();
+        let patches = d.patch_make1(&t1, &t2);
+        let (chars, expected) = d.patch_apply(&mut patches.clone(), &target);
+        let chars: String = chars.into_iter().collect();
+        assert_eq!(
+            d.patch_apply_str(&patches, &target),
+            (chars.clone(), expected.clone())
+        );
+        let mut in_place = target.clone();
+        assert_eq!(d.patch_apply_in_place(&patches, &mut in_place), expected);
+        assert_eq!(in_place, chars);
+    }
+}
+
+/// Applying patches and then their inverse round-trips, and inverting
+/// twice gives the patches back.
+#[test]
+fn inverted_patches_undo() {
+    let alphabet = ["a", "b", "c", "\u{e9}", "\n", " "];
+    let mut rng = Lcg(1301);
+    for i in 0..400 {
+        let len = (rng.next() % 120) as usize;
+        let t1 = gen_text(&mut rng, &alphabet, len);
+        let t2 = if i % 2 == 0 {
+            mutate(&mut rng, &alphabet, &t1)
+        } else {
+            let len = (rng.next() % 120) as usize;
+            gen_text(&mut rng, &alphabet, len)
+        };
+        let d = Dmp::new();
+        let patches = d.patch_make1(&t1, &t2);
+        let (applied, results) = d.patch_apply_str(&patches, &t1);
+        assert_eq!(applied, t2);
+        assert!(results.iter().all(|&ok| ok));
+
+        // Each inverse patch lands verbatim where it says it does.
+        let inverse = d.patch_invert(&patches);
+        let (undone, reports) = d.patch_apply_report(&inverse, &applied);
+        assert_eq!(undone, t1);
+        assert!(reports.iter().all(|r| r.is_exact()), "{:?}", reports);
+        assert_eq!(d.patch_invert(&inverse), patches);
+    }
+}
+
+/// Composed diffs and patches take the first text straight to the third,
+/// and composed diffs are merged (no two neighbours share an operation).
+#[test]
+fn composed_edits_chain() {
+    let alphabet = ["a", "b", "c", "\u{e9}", "\n", " "];
+    let mut rng = Lcg(1409);
+    for i in 0..400 {
+        let len = (rng.next() % 120) as usize;
+        let mut texts = vec![gen_text(&mut rng, &alphabet, len)];
+        for _ in 0..2 {
+            let next = if i % 4 == 0 {
+                let len = (rng.next() % 120) as usize;
+                gen_text(&mut rng, &alphabet, len)
+            } else {
+                mutate(&mut rng, &alphabet, texts.last().unwrap())
+            };
+            texts.push(next);
+        }
+        let (t1, t2, t3) = (&texts[0], &texts[1], &texts[2]);
+        let d = Dmp::new();
+
+        let diffs = d
+            .diff_compose(&d.diff_main(t1, t2, false), &d.diff_main(t2, t3, false))
+            .unwrap();
+        assert_eq!(rebuild(&diffs), (t1.clone(), t3.clone()));
+        for pair in diffs.windows(2) {
+            assert_ne!(pair[0].operation, pair[1].operation, "{:?}", diffs);
+        }
+
+        // Each composed patch lands verbatim where it says it does.
+        let patches = d
+            .patch_compose(&d.patch_make1(t1, t2), &d.patch_make1(t2, t3))
+            .unwrap();
+        let (applied, reports) = d.patch_apply_report(&patches, t1);
+        assert_eq!(&applied, t3);
+        assert!(reports.iter().all(|r| r.is_exact()), "{:?}", reports);
+    }
+}
+
 #[cfg(feature = "grapheme")]
 mod grapheme_props {
     use super::*;
@@ -103,12 +449,12 @@ mod grapheme_props {
 
             let parts1: usize = diffs
                 .iter()
-                .filter(|d| d.operation != 1)
+                .filter(|d| d.operation != Operation::Insert)
                 .map(|d| grapheme_count(&d.text))
                 .sum();
             let parts2: usize = diffs
                 .iter()
-                .filter(|d| d.operation != -1)
+                .filter(|d| d.operation != Operation::Delete)
                 .map(|d| grapheme_count(&d.text))
                 .sum();
             assert_eq!(
@@ -126,7 +472,7 @@ mod grapheme_props {
             d.diff_cleanup_semantic(&mut diffs);
             let parts1: usize = diffs
                 .iter()
-                .filter(|d| d.operation != 1)
+                .filter(|d| d.operation != Operation::Insert)
                 .map(|d| grapheme_count(&d.text))
                 .sum();
             assert_eq!(
@@ -184,7 +530,7 @@ mod grapheme_props {
             let t1 = gen_text(&mut rng, &alphabet, len);
             let len = (rng.next() % 60) as usize;
             let t2 = gen_text(&mut rng, &alphabet, len);
-            let mut char_mode = Dmp::new();
+            let char_mode = Dmp::new();
             let mut grapheme_mode = Dmp::new();
             grapheme_mode.segmentation = Segmentation::Grapheme;
             // Single-char clusters pack to themselves, so both modes run the
@@ -3,7 +3,7 @@
 // mode deliberately snaps edit boundaries to word boundaries first, which is
 // why it is off by default and the oracle corpus never sees it).
 
-use diff_match_patch::Dmp;
+use diff_match_patch::{Dmp, Operation};
 
 /// A rename-shaped fixture: every line changes by a few characters, the
 /// worst case for line mode and the case word mode exists for.
@@ -50,7 +50,7 @@ fn word_mode_finds_the_unchanged_words() {
     let diffs = dmp.diff_main(&t1, &t2, true);
     let equal_chars: usize = diffs
         .iter()
-        .filter(|d| d.operation == 0)
+        .filter(|d| d.operation == Operation::Equal)
         .map(|d| d.text.chars().count())
         .sum();
     // "rows", "cfg", "let", the operators and indentation are all shared.
@@ -90,12 +90,12 @@ fn word_mode_trivial_cases() {
     let t = "the same text with words ".repeat(10);
     let diffs = dmp.diff_main(&t, &t, true);
     assert_eq!(diffs.len(), 1);
-    assert_eq!(diffs[0].operation, 0);
+    assert_eq!(diffs[0].operation, Operation::Equal);
     assert_eq!(diffs[0].text, t);
 
     let diffs = dmp.diff_main("", &t, true);
     assert_eq!(diffs.len(), 1);
-    assert_eq!(diffs[0].operation, 1);
+    assert_eq!(diffs[0].operation, Operation::Insert);
 }
 
 #[cfg(feature = "grapheme")]
@@ -122,3 +122,186 @@ fn word_mode_stays_cluster_atomic_in_grapheme_mode() {
         );
     }
 }
+
+// `diff_words`: the token-atomic entry point. The cases below are the
+// acceptance suite from the word-diff design; each changed token must come
+// out whole.
+
+/// Render as `[-deleted-]{+inserted+}`, the notation the design uses.
+fn wdiff(dmp: &mut Dmp, old: &str, new: &str) -> String {
+    let mut diffs = dmp.diff_words(old, new);
+    assert_eq!(dmp.diff_text1(&mut diffs), old, "diff must rebuild old");
+    assert_eq!(dmp.diff_text2(&mut diffs), new, "diff must rebuild new");
+    dmp.diff_pretty_wdiff(&diffs, &Default::default())
+}
+
+#[test]
+fn diff_words_keeps_changed_tokens_whole() {
+    let mut dmp = Dmp::new();
+    assert_eq!(
+        wdiff(&mut dmp, "committed", "commits"),
+        "[-committed-]{+commits+}"
+    );
+    assert_eq!(wdiff(&mut dmp, "v1.2.3", "v1.2.4"), "[-v1.2.3-]{+v1.2.4+}");
+    assert_eq!(wdiff(&mut dmp, "$19.99", "$24.99"), "[-$19.99-]{+$24.99+}");
+    assert_eq!(
+        wdiff(&mut dmp, "release v1.2.3 is out", "release v1.2.4 is out"),
+        "release [-v1.2.3-]{+v1.2.4+} is out"
+    );
+}
+
+#[test]
+fn diff_words_ignores_coincidental_char_structure() {
+    // The char diff shares a spurious "0:"; the word diff replaces the value.
+    let mut dmp = Dmp::new();
+    let diffs = dmp.diff_main("0:00", "30:58", false);
+    assert!(diffs.iter().any(|d| d.operation == Operation::Equal));
+    assert_eq!(wdiff(&mut dmp, "0:00", "30:58"), "[-0:00-]{+30:58+}");
+}
+
+#[test]
+fn diff_words_leaves_cleanup_to_the_caller() {
+    // Token-atomic by default: the aligned space is kept, so the moved word
+    // shows as both removed and added.
+    let mut dmp = Dmp::new();
+    assert_eq!(
+        wdiff(&mut dmp, "Item 1", "First Item"),
+        "[-Item-]{+First+} [-1-]{+Item+}"
+    );
+    // Semantic cleanup is one call away and rescues the reorder.
+    let mut diffs = dmp.diff_words("Item 1", "First Item");
+    dmp.diff_cleanup_semantic(&mut diffs);
+    assert_eq!(
+        dmp.diff_pretty_wdiff(&diffs, &Default::default()),
+        "{+First +}Item[- 1-]"
+    );
+}
+
+#[test]
+fn diff_words_edit_regions_yield_whole_tokens() {
+    // Collect tokens per edit region; concatenating all insertions would
+    // fuse tokens across the unchanged text between them.
+    let mut dmp = Dmp::new();
+    let old = "Price: $19.99 — In stock";
+    let new = "Price: $24.99 — Sold out";
+    assert_eq!(
+        wdiff(&mut dmp, old, new),
+        "Price: [-$19.99-]{+$24.99+} — [-In-]{+Sold+} [-stock-]{+out+}"
+    );
+    let diffs = dmp.diff_words(old, new);
+    let added: Vec<String> = diffs
+        .iter()
+        .filter(|d| d.operation == Operation::Insert)
+        .flat_map(|d| d.text.split_whitespace())
+        .map(str::to_string)
+        .collect();
+    assert_eq!(added, vec!["$24.99", "Sold", "out"]);
+    // The helpers do the same.
+    assert_eq!(dmp.added_tokens(&diffs), vec!["$24.99", "Sold", "out"]);
+    assert_eq!(dmp.removed_tokens(&diffs), vec!["$19.99", "In", "stock"]);
+    // Edits that span several words still split per word.
+    let diffs = dmp.diff_words("sold out now", "in stock now");
+    assert_eq!(dmp.added_tokens(&diffs), vec!["in", "stock"]);
+    assert_eq!(dmp.removed_tokens(&diffs), vec!["sold", "out"]);
+}
+
+#[test]
+fn diff_words_never_splits_a_cluster() {
+    let mut dmp = Dmp::new();
+    // CRLF is one whitespace token, on the ASCII path and the char path.
+    assert_eq!(wdiff(&mut dmp, "a\r\nb", "a\nb"), "a[-\r\n-]{+\n+}b");
+    assert_eq!(wdiff(&mut dmp, "é\r\nb", "é\nb"), "é[-\r\n-]{+\n+}b");
+    // A combining mark on a space belongs to the space's token.
+    assert_eq!(wdiff(&mut dmp, "x \u{301}y", "x y"), "x[- \u{301}-]{+ +}y");
+    assert_eq!(
+        wdiff(&mut dmp, "cafe\u{301} ok", "cafe ok"),
+        "[-cafe\u{301}-]{+cafe+} ok"
+    );
+}
+
+#[test]
+fn diff_words_trivial_cases() {
+    let mut dmp = Dmp::new();
+    assert!(dmp.diff_words("", "").is_empty());
+    assert_eq!(wdiff(&mut dmp, "same words", "same words"), "same words");
+    assert_eq!(wdiff(&mut dmp, "", "new words"), "{+new words+}");
+    assert_eq!(wdiff(&mut dmp, "old words", ""), "[-old words-]");
+}
+
+#[test]
+fn diff_words_defaults_to_whitespace_vocabulary() {
+    use diff_match_patch::WordVocabulary;
+    assert_eq!(Dmp::new().word_vocabulary, WordVocabulary::Whitespace);
+}
+
+#[cfg(feature = "grapheme")]
+#[test]
+fn diff_words_unicode_vocabulary() {
+    use diff_match_patch::WordVocabulary;
+    let mut dmp = Dmp::new();
+    dmp.word_vocabulary = WordVocabulary::Unicode;
+    // Punctuation splits off, so the bare word is the changed token.
+    assert_eq!(
+        wdiff(&mut dmp, "the lazy dog.", "the lazy cat."),
+        "the lazy [-dog-]{+cat+}."
+    );
+    // Ideographic text diffs per ideograph instead of as one run.
+    assert_eq!(
+        wdiff(&mut dmp, "我喜欢猫。", "我喜欢狗。"),
+        "我喜欢[-猫-]{+狗+}。"
+    );
+    // The token helpers cut UAX #29 words too.
+    let diffs = dmp.diff_words("costs $19.99", "costs $24.99!");
+    assert_eq!(dmp.added_tokens(&diffs), vec!["24.99", "!"]);
+    // Structured values are split apart, unlike the default vocabulary.
+    assert_eq!(wdiff(&mut dmp, "$19.99", "$24.99"), "$[-19.99-]{+24.99+}");
+}
+
+#[test]
+fn diff_words_past_the_surrogate_gap() {
+    // More distinct words than ids below U+D800, so some placeholders sit
+    // past the surrogate gap and must map back to their own words.
+    let dmp = Dmp::new();
+    let mut words: Vec<String> = (0..60_000).map(|i| format!("w{i}")).collect();
+    let old = words.join(" ");
+    words[59_000] = "changed".to_string();
+    let new = words.join(" ");
+    let diffs = dmp.diff_words(&old, &new);
+    assert_eq!(dmp.removed_tokens(&diffs), vec!["w59000"]);
+    assert_eq!(dmp.added_tokens(&diffs), vec!["changed"]);
+    let mut text1 = String::new();
+    let mut text2 = String::new();
+    for d in &diffs {
+        if d.operation != Operation::Insert {
+            text1 += &d.text;
+        }
+        if d.operation != Operation::Delete {
+            text2 += &d.text;
+        }
+    }
+    assert_eq!(text1, old);
+    assert_eq!(text2, new);
+}
+
+#[test]
+fn diff_tokens_past_the_surrogate_gap() {
+    use diff_match_patch::LineTokenizer;
+    let dmp = Dmp::new();
+    let mut lines: Vec<String> = (0..60_000).map(|i| format!("line {i}\n")).collect();
+    let old = lines.concat();
+    lines[59_000] = "changed\n".to_string();
+    let new = lines.concat();
+    let diffs = dmp.diff_tokens(&old, &new, &LineTokenizer);
+    let changed: Vec<(Operation, &str)> = diffs
+        .iter()
+        .filter(|d| d.operation != Operation::Equal)
+        .map(|d| (d.operation, d.text.as_str()))
+        .collect();
+    assert_eq!(
+        changed,
+        vec![
+            (Operation::Delete, "line 59000\n"),
+            (Operation::Insert, "changed\n")
+        ]
+    );
+}